@@ -6,21 +6,29 @@ use ewebsock::{WsEvent, WsMessage, WsReceiver, WsSender};
 use log::{error, warn};
 use rtpeeker_common::{Request, Response, Source, StreamKey};
 
+use events_table::EventsTable;
+use frames_table::FramesTable;
+use lip_sync_view::LipSyncView;
 use packets_table::PacketsTable;
 use rtcp_packets_table::RtcpPacketsTable;
 use rtp_packets_table::RtpPacketsTable;
 use rtp_streams_table::RtpStreamsTable;
 use tab::Tab;
+use twcc_view::TwccView;
 
 use crate::streams::RefStreams;
 use rtp_streams_plot::RtpStreamsPlot;
 
+mod events_table;
+mod frames_table;
+mod lip_sync_view;
 mod packets_table;
 mod rtcp_packets_table;
 mod rtp_packets_table;
 mod rtp_streams_plot;
 mod rtp_streams_table;
 mod tab;
+mod twcc_view;
 
 const SOURCE_KEY: &str = "source";
 const TAB_KEY: &str = "tab";
@@ -41,7 +49,11 @@ pub struct App {
     rtp_packets_table: RtpPacketsTable,
     rtcp_packets_table: RtcpPacketsTable,
     rtp_streams_table: RtpStreamsTable,
+    frames_table: FramesTable,
     rtp_streams_plot: RtpStreamsPlot,
+    lip_sync_view: LipSyncView,
+    twcc_view: TwccView,
+    events_table: EventsTable,
 }
 
 impl eframe::App for App {
@@ -58,8 +70,17 @@ impl eframe::App for App {
             Tab::Packets => self.packets_table.ui(ctx),
             Tab::RtpPackets => self.rtp_packets_table.ui(ctx),
             Tab::RtcpPackets => self.rtcp_packets_table.ui(ctx),
-            Tab::Streams => self.rtp_streams_table.ui(ctx),
+            Tab::Streams => {
+                if let Some(target) = self.rtp_streams_table.ui(ctx) {
+                    self.rtp_streams_plot.show_target(target);
+                    self.tab = Tab::Plot;
+                }
+            }
+            Tab::Frames => self.frames_table.ui(ctx),
             Tab::Plot => self.rtp_streams_plot.ui(ctx),
+            Tab::LipSync => self.lip_sync_view.ui(ctx),
+            Tab::Twcc => self.twcc_view.ui(ctx),
+            Tab::Events => self.events_table.ui(ctx),
         };
     }
 }
@@ -80,7 +101,11 @@ impl App {
         let rtp_packets_table = RtpPacketsTable::new(streams.clone());
         let rtcp_packets_table = RtcpPacketsTable::new(streams.clone());
         let rtp_streams_table = RtpStreamsTable::new(streams.clone(), ws_sender.clone());
+        let frames_table = FramesTable::new(streams.clone());
         let rtp_streams_plot = RtpStreamsPlot::new(streams.clone());
+        let lip_sync_view = LipSyncView::new(streams.clone());
+        let twcc_view = TwccView::new(streams.clone());
+        let events_table = EventsTable::new(streams.clone(), ws_sender.clone());
 
         let (tab, selected_source) = get_initial_state(cc);
 
@@ -96,14 +121,18 @@ impl App {
             rtp_packets_table,
             rtcp_packets_table,
             rtp_streams_table,
+            frames_table,
             rtp_streams_plot,
+            lip_sync_view,
+            twcc_view,
+            events_table,
         }
     }
 
     fn build_side_panel(&mut self, ctx: &egui::Context) {
         let mut style = (*ctx.style()).clone();
         style.spacing.item_spacing = (0.0, 8.0).into();
-        for (_text_style, font_id) in style.text_styles.iter_mut() {
+        for font_id in style.text_styles.values_mut() {
             font_id.size = 20.0;
         }
 
@@ -264,13 +293,21 @@ impl App {
                     self.sources = sources;
                 }
                 Response::Sdp(stream_key, sdp) => {
-                    let mut streams = self.streams.borrow_mut();
-                    if let Some(stream) = streams.streams.get_mut(&stream_key) {
-                        stream.add_sdp(sdp);
-                    }
+                    self.streams.borrow_mut().add_sdp(stream_key, sdp);
+                }
+                Response::SdpRemoved(stream_key) => {
+                    self.streams.borrow_mut().remove_sdp(&stream_key);
+                }
+                Response::DecodeRules(rules) => {
+                    self.streams.borrow_mut().decode_rules = rules;
+                }
+                Response::QualityRules(rules) => {
+                    self.streams.borrow_mut().set_quality_rules(rules);
                 }
             }
         }
+
+        self.streams.borrow_mut().recalculate_if_outdated();
     }
 
     fn refetch_packets(&mut self) {
@@ -0,0 +1,160 @@
+use crate::streams::RefStreams;
+use eframe::epaint::Color32;
+use egui::widgets::TextEdit;
+use egui::RichText;
+use egui_extras::{Column, TableBody, TableBuilder};
+use ewebsock::{WsMessage, WsSender};
+use rtpeeker_common::{QualityRule, Request};
+
+const RULE_HINT: &str =
+    "e.g. jitter > 30 ms, loss > 2% over 5 s, no packets for 500 ms, SSRC changed";
+
+pub struct EventsTable {
+    streams: RefStreams,
+    ws_sender: WsSender,
+    rule_buffer: String,
+    is_rule_invalid: bool,
+}
+
+impl EventsTable {
+    pub fn new(streams: RefStreams, ws_sender: WsSender) -> Self {
+        Self {
+            streams,
+            ws_sender,
+            rule_buffer: String::new(),
+            is_rule_invalid: false,
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("quality_rules").show(ctx, |ui| {
+            self.build_rules(ui);
+        });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            self.build_table(ui);
+        });
+    }
+
+    fn build_rules(&mut self, ui: &mut egui::Ui) {
+        let mut rules = self.streams.borrow().get_quality_rules().to_vec();
+        let mut is_changed = false;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(RichText::new("Rules:").strong());
+            if rules.is_empty() {
+                ui.label("None");
+            }
+            let mut removed = None;
+            for (ix, rule) in rules.iter().enumerate() {
+                if ui.button("🗑").on_hover_text("Remove the rule").clicked() {
+                    removed = Some(ix);
+                }
+                ui.label(rule.to_string());
+                ui.separator();
+            }
+            if let Some(ix) = removed {
+                rules.remove(ix);
+                is_changed = true;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let text_edit = TextEdit::singleline(&mut self.rule_buffer)
+                .font(egui::style::TextStyle::Monospace)
+                .desired_width(400.0)
+                .hint_text(RULE_HINT);
+            let resp = ui.add(text_edit);
+            let is_submitted = resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if resp.changed() {
+                self.is_rule_invalid = false;
+            }
+
+            if ui.button("Add").on_hover_text("Add the rule").clicked() || is_submitted {
+                match QualityRule::from_string(&self.rule_buffer) {
+                    Some(rule) => {
+                        rules.push(rule);
+                        is_changed = true;
+                        self.rule_buffer.clear();
+                    }
+                    None => self.is_rule_invalid = true,
+                }
+            }
+            if self.is_rule_invalid {
+                ui.label(RichText::new("Invalid rule").color(Color32::RED));
+            }
+        });
+
+        if is_changed {
+            self.send_request(Request::SetQualityRules(rules));
+        }
+    }
+
+    fn build_table(&mut self, ui: &mut egui::Ui) {
+        let header_labels = [
+            ("Time", "Event timestamp relative to the first packet"),
+            ("Stream", "Alias of the stream the event occurred in"),
+            ("SSRC", "SSRC of the stream"),
+            ("Rule", "Quality rule that fired the event"),
+            ("Description", "Value that violated the rule"),
+        ];
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .stick_to_bottom(true)
+            .column(Column::remainder().at_least(80.0))
+            .columns(Column::remainder().at_least(60.0), 2)
+            .column(Column::remainder().at_least(160.0))
+            .column(Column::remainder().at_least(250.0))
+            .header(30.0, |mut header| {
+                header_labels.iter().for_each(|(label, desc)| {
+                    header.col(|ui| {
+                        ui.heading(label.to_string())
+                            .on_hover_text(desc.to_string());
+                    });
+                });
+            })
+            .body(|body| {
+                self.build_table_body(body);
+            });
+    }
+
+    fn build_table_body(&self, body: TableBody) {
+        let streams = self.streams.borrow();
+        let Some(first_packet) = streams.packets.first() else {
+            return;
+        };
+        let first_time = first_packet.timestamp;
+        let events = streams.get_events();
+
+        body.rows(25.0, events.len(), |ix, mut row| {
+            let (stream, event) = events[ix];
+
+            row.col(|ui| {
+                let time = event.time.saturating_sub(first_time);
+                ui.label(format!("{:.4}", time.as_secs_f64()));
+            });
+            row.col(|ui| {
+                ui.label(stream.alias.as_str());
+            });
+            row.col(|ui| {
+                ui.label(format!("{:x}", stream.ssrc));
+            });
+            row.col(|ui| {
+                ui.label(event.rule.to_string());
+            });
+            row.col(|ui| {
+                ui.label(event.description.as_str());
+            });
+        });
+    }
+
+    fn send_request(&mut self, request: Request) {
+        let Ok(msg) = request.encode() else {
+            log::error!("Failed to encode a request message");
+            return;
+        };
+        let msg = WsMessage::Binary(msg);
+
+        self.ws_sender.send(msg);
+    }
+}
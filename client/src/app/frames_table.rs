@@ -0,0 +1,173 @@
+use super::rtp_packets_table::format_boolean;
+use crate::streams::frames::Frame;
+use crate::streams::RefStreams;
+use egui::ComboBox;
+use egui_extras::{Column, TableBody, TableBuilder};
+use rtpeeker_common::rtp::payload_type::MediaType;
+use rtpeeker_common::StreamKey;
+
+pub struct FramesTable {
+    streams: RefStreams,
+    selected_key: Option<StreamKey>,
+}
+
+impl FramesTable {
+    pub fn new(streams: RefStreams) -> Self {
+        Self {
+            streams,
+            selected_key: None,
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("frames_stream_picker").show(ctx, |ui| {
+            self.build_stream_picker(ui);
+        });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            self.build_table(ui);
+        });
+    }
+
+    fn build_stream_picker(&mut self, ui: &mut egui::Ui) {
+        let streams = self.streams.borrow();
+        let mut video_streams: Vec<_> = streams
+            .streams
+            .iter()
+            .filter(|(_, stream)| matches!(stream.get_media_type(), Some(MediaType::Video)))
+            .map(|(key, stream)| (*key, stream.alias.clone()))
+            .collect();
+        video_streams.sort_by(|(_, a), (_, b)| (a.len(), a).cmp(&(b.len(), b)));
+
+        let selected = video_streams
+            .iter()
+            .find(|(key, _)| Some(*key) == self.selected_key)
+            .or(video_streams.first());
+        self.selected_key = selected.map(|(key, _)| *key);
+        let selected_text =
+            selected.map_or("No video streams".to_string(), |(_, alias)| alias.clone());
+
+        ui.horizontal(|ui| {
+            ui.label("Video stream: ");
+            ComboBox::from_id_source("frames_stream_picker")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for (key, alias) in video_streams {
+                        ui.selectable_value(&mut self.selected_key, Some(key), alias);
+                    }
+                });
+        });
+    }
+
+    fn build_table(&mut self, ui: &mut egui::Ui) {
+        let header_labels = [
+            ("No.", "Frame number within the stream"),
+            (
+                "Time",
+                "Arrival of the frame's first packet relative to the first frame",
+            ),
+            ("Timestamp", "RTP timestamp shared by the frame's packets"),
+            ("Packets", "Number of received packets of the frame"),
+            (
+                "Sequence numbers",
+                "Extended sequence numbers of the frame's first and last packet",
+            ),
+            ("Size", "Sum of the payload lengths of the frame's packets"),
+            (
+                "Transmission",
+                "Time between the arrival of the frame's first and last packet",
+            ),
+            (
+                "Jitter",
+                "Frame inter-arrival jitter, using the arrival of the frame's last packet",
+            ),
+            (
+                "Keyframe",
+                "Frame is a keyframe (if payload format is known)",
+            ),
+            ("Complete", "All of the frame's packets were received"),
+        ];
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .stick_to_bottom(true)
+            .column(Column::remainder().at_least(50.0))
+            .columns(Column::remainder().at_least(80.0), 3)
+            .column(Column::remainder().at_least(130.0))
+            .columns(Column::remainder().at_least(80.0), 5)
+            .header(30.0, |mut header| {
+                header_labels.iter().for_each(|(label, desc)| {
+                    header.col(|ui| {
+                        ui.heading(label.to_string())
+                            .on_hover_text(desc.to_string());
+                    });
+                });
+            })
+            .body(|body| {
+                self.build_table_body(body);
+            });
+    }
+
+    fn build_table_body(&mut self, body: TableBody) {
+        let streams = self.streams.borrow();
+        let Some(stats) = self
+            .selected_key
+            .and_then(|key| streams.streams.get(&key))
+            .and_then(|stream| stream.get_frame_stats())
+        else {
+            return;
+        };
+
+        let first_time = stats.frames.first().map(|frame| frame.first_time);
+        body.rows(25.0, stats.frames.len(), |ix, mut row| {
+            let frame = &stats.frames[ix];
+            let first_time = first_time.unwrap();
+
+            row.col(|ui| {
+                ui.label(ix.to_string());
+            });
+            row.col(|ui| {
+                let time = frame.first_time.saturating_sub(first_time);
+                ui.label(format!("{:.4}", time.as_secs_f64()));
+            });
+            row.col(|ui| {
+                ui.label(frame.timestamp.to_string());
+            });
+            row.col(|ui| {
+                ui.label(frame.packets.to_string());
+            });
+            row.col(|ui| {
+                ui.label(format!(
+                    "{} - {}",
+                    frame.first_sequence_number, frame.last_sequence_number
+                ));
+            });
+            row.col(|ui| {
+                ui.label(format!("{} B", frame.bytes));
+            });
+            row.col(|ui| {
+                let transmission = frame.last_time - frame.first_time;
+                ui.label(format!("{:.3} ms", transmission.as_secs_f64() * 1000.0));
+            });
+            row.col(|ui| {
+                let jitter_label = match frame.jitter {
+                    Some(jitter) => format!("{:.3} ms", jitter * 1000.0),
+                    None => "N/A".to_string(),
+                };
+                ui.label(jitter_label);
+            });
+            row.col(|ui| {
+                ui.label(format_boolean(frame.is_keyframe));
+            });
+            row.col(|ui| {
+                build_complete_label(ui, frame);
+            });
+        });
+    }
+}
+
+fn build_complete_label(ui: &mut egui::Ui, frame: &Frame) {
+    let resp = ui.label(format_boolean(frame.is_complete));
+    if !frame.is_complete {
+        resp.on_hover_text("Some of the frame's packets were lost");
+    }
+}
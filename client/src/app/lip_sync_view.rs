@@ -0,0 +1,147 @@
+use crate::streams::lip_sync::{is_in_sync, LipSyncStats, MAX_AUDIO_LAG, MAX_AUDIO_LEAD};
+use crate::streams::{RefStreams, Streams};
+use eframe::epaint::Color32;
+use egui::plot::{HLine, Legend, Line, LineStyle, Plot, PlotPoints};
+use egui::RichText;
+use std::collections::BTreeMap;
+
+pub struct LipSyncView {
+    streams: RefStreams,
+}
+
+impl LipSyncView {
+    pub fn new(streams: RefStreams) -> Self {
+        Self { streams }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let streams = self.streams.borrow();
+        let stats = streams.get_lip_sync_stats();
+
+        egui::TopBottomPanel::top("lip_sync_summary")
+            .resizable(true)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    build_summary(ui, &streams, &stats);
+                });
+            });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            build_plot(ui, &streams, &stats);
+        });
+    }
+}
+
+fn build_summary(ui: &mut egui::Ui, streams: &Streams, stats: &[LipSyncStats]) {
+    if stats.is_empty() {
+        ui.label(
+            "No audio and video streams with the same CNAME and RTCP Sender Reports to compare",
+        );
+        return;
+    }
+
+    let mut participants: BTreeMap<&str, Vec<&LipSyncStats>> = BTreeMap::new();
+    for pair_stats in stats {
+        participants
+            .entry(&pair_stats.cname)
+            .or_default()
+            .push(pair_stats);
+    }
+
+    for (cname, pairs) in participants {
+        ui.add_space(5.0);
+        ui.label(RichText::new(format!("CNAME {}", cname)).strong());
+        egui::Grid::new(cname)
+            .striped(true)
+            .num_columns(5)
+            .show(ui, |ui| {
+                ui.label("Streams");
+                ui.label("Mean offset")
+                    .on_hover_text("Positive if the audio is presented later than the video");
+                ui.label("Max offset");
+                ui.label("Out of sync")
+                    .on_hover_text("Share of the time with the offset exceeding the tolerance");
+                ui.label("Status");
+                ui.end_row();
+
+                for pair_stats in pairs {
+                    ui.label(get_pair_name(streams, pair_stats));
+                    let mean_skew = pair_stats.get_mean_skew();
+                    ui.label(format!("{:+.1} ms", mean_skew * 1000.0));
+                    ui.label(format!("{:+.1} ms", pair_stats.get_max_skew() * 1000.0));
+                    let out_of_sync = pair_stats.get_out_of_sync_fraction() * 100.0;
+                    ui.label(format!("{:.1}%", out_of_sync));
+                    ui.label(get_status(mean_skew));
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+fn get_status(skew: f64) -> RichText {
+    if is_in_sync(skew) {
+        return RichText::new("✔ In sync");
+    }
+
+    let text = if skew > 0.0 {
+        format!("⚠ Audio lags by {:.0} ms", skew * 1000.0)
+    } else {
+        format!("⚠ Audio leads by {:.0} ms", -skew * 1000.0)
+    };
+    RichText::new(text).color(Color32::RED)
+}
+
+fn build_plot(ui: &mut egui::Ui, streams: &Streams, stats: &[LipSyncStats]) {
+    let first_time = stats
+        .iter()
+        .filter_map(|pair_stats| pair_stats.skews.first())
+        .map(|(time, _)| *time)
+        .min_by(f64::total_cmp)
+        .unwrap_or(0.0);
+
+    Plot::new("lip-sync-plot")
+        .legend(Legend::default())
+        .label_formatter(|name, value| {
+            format!(
+                "{}\ntime: {:.0} s\noffset = {:.1} ms",
+                name, value.x, value.y
+            )
+        })
+        .show(ui, |plot_ui| {
+            plot_ui.hline(
+                HLine::new(MAX_AUDIO_LAG * 1000.0)
+                    .name("Tolerance")
+                    .color(Color32::RED)
+                    .style(LineStyle::dashed_loose()),
+            );
+            plot_ui.hline(
+                HLine::new(-MAX_AUDIO_LEAD * 1000.0)
+                    .name("Tolerance")
+                    .color(Color32::RED)
+                    .style(LineStyle::dashed_loose()),
+            );
+
+            for pair_stats in stats {
+                let points: PlotPoints = pair_stats
+                    .skews
+                    .iter()
+                    .map(|(time, skew)| [time - first_time, skew * 1000.0])
+                    .collect();
+                plot_ui.line(Line::new(points).name(get_pair_name(streams, pair_stats)));
+            }
+        });
+}
+
+fn get_pair_name(streams: &Streams, pair_stats: &LipSyncStats) -> String {
+    let alias = |key| {
+        streams
+            .streams
+            .get(key)
+            .map(|stream| stream.alias.clone())
+            .unwrap_or_default()
+    };
+    format!(
+        "{} (audio) / {} (video)",
+        alias(&pair_stats.audio_key),
+        alias(&pair_stats.video_key)
+    )
+}
@@ -2,6 +2,7 @@ use crate::streams::RefStreams;
 use egui::widgets::TextEdit;
 use egui_extras::{Column, TableBody, TableBuilder};
 use ewebsock::{WsMessage, WsSender};
+use rtpeeker_common::decode_rule::{DecodeRule, RuleTarget};
 use rtpeeker_common::packet::{Packet, SessionProtocol};
 use rtpeeker_common::Request;
 
@@ -9,6 +10,7 @@ pub struct PacketsTable {
     streams: RefStreams,
     ws_sender: WsSender,
     filter_buffer: String,
+    rules_window_open: bool,
 }
 
 impl PacketsTable {
@@ -17,6 +19,7 @@ impl PacketsTable {
             streams,
             ws_sender,
             filter_buffer: String::new(),
+            rules_window_open: false,
         }
     }
 
@@ -27,6 +30,35 @@ impl PacketsTable {
         egui::CentralPanel::default().show(ctx, |ui| {
             self.build_table(ui);
         });
+        self.build_rules_window(ctx);
+    }
+
+    fn build_rules_window(&mut self, ctx: &egui::Context) {
+        let mut requests = Vec::new();
+        let streams = self.streams.borrow();
+
+        egui::Window::new("Decode as rules")
+            .open(&mut self.rules_window_open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                if streams.decode_rules.is_empty() {
+                    ui.label("No rules, add one from the packet's context menu");
+                }
+                // the most recent rule takes precedence, so it goes first
+                for rule in streams.decode_rules.iter().rev() {
+                    ui.horizontal(|ui| {
+                        if ui.button("🗑").on_hover_text("Remove the rule").clicked() {
+                            requests.push(Request::RemoveDecodeRule(rule.target));
+                        }
+                        ui.label(rule.to_string());
+                    });
+                }
+            });
+
+        std::mem::drop(streams);
+        requests
+            .into_iter()
+            .for_each(|req| self.send_parse_request(req));
     }
 
     fn build_filter(&mut self, ui: &mut egui::Ui) {
@@ -39,6 +71,14 @@ impl PacketsTable {
             // TODO: implement the actuall filtering
             ui.button("↻").on_hover_text("Reset the filter");
             ui.button("⏵").on_hover_text("Apply the filter");
+            let rule_count = self.streams.borrow().decode_rules.len();
+            if ui
+                .button(format!("Decode as ({})", rule_count))
+                .on_hover_text("Show the rules overriding how packets are parsed")
+                .clicked()
+            {
+                self.rules_window_open = true;
+            }
             ui.add(text_edit);
         });
     }
@@ -130,7 +170,7 @@ impl PacketsTable {
 
     fn build_parse_menu(&self, ui: &mut egui::Ui, packet: &Packet) -> Option<Request> {
         let mut request = None;
-        ui.label(format!("Parse {} as:", &packet.id));
+        ui.label(format!("Parse {} as:", packet.id));
         SessionProtocol::all().iter().for_each(|packet_type| {
             let is_type = packet.session_protocol == *packet_type;
             if ui.radio(is_type, packet_type.to_string()).clicked() {
@@ -138,6 +178,32 @@ impl PacketsTable {
             }
         });
         ui.separator();
+
+        let mut targets = vec![
+            RuleTarget::Flow(
+                packet.source_addr,
+                packet.destination_addr,
+                packet.transport_protocol,
+            ),
+            RuleTarget::Port(packet.transport_protocol, packet.source_addr.port()),
+            RuleTarget::Port(packet.transport_protocol, packet.destination_addr.port()),
+        ];
+        // source and destination ports are often the same
+        targets.dedup();
+        for target in targets {
+            ui.menu_button(format!("Decode {} as", target), |ui| {
+                SessionProtocol::all().iter().for_each(|protocol| {
+                    if ui.button(protocol.to_string()).clicked() {
+                        ui.close_menu();
+                        request = Some(Request::AddDecodeRule(DecodeRule {
+                            target,
+                            protocol: *protocol,
+                        }));
+                    }
+                });
+            });
+        }
+        ui.separator();
         ui.label("This will have effect on every client!");
 
         request
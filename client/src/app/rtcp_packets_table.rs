@@ -131,6 +131,7 @@ fn get_row_height(packet: &RtcpPacket) -> f32 {
             0 => 4.7,
             _ => 11.0,
         },
+        RtcpPacket::TransportFeedback(_) => 3.0,
         _ => 1.0,
     };
 
@@ -143,6 +144,7 @@ fn build_packet(ui: &mut Ui, packet: &RtcpPacket) {
         RtcpPacket::ReceiverReport(report) => build_receiver_report(ui, report),
         RtcpPacket::SourceDescription(desc) => build_source_description(ui, desc),
         RtcpPacket::Goodbye(bye) => build_goodbye(ui, bye),
+        RtcpPacket::TransportFeedback(feedback) => build_transport_feedback(ui, feedback),
         _ => {
             ui.label("Unsupported");
         }
@@ -245,6 +247,23 @@ fn build_goodbye(ui: &mut Ui, bye: &Goodbye) {
     build_label(ui, "Reason:", bye.reason.clone());
 }
 
+fn build_transport_feedback(ui: &mut Ui, feedback: &TransportFeedback) {
+    ui.horizontal(|ui| {
+        ui.vertical(|ui| {
+            build_label(ui, "Source:", format!("{:x}", feedback.sender_ssrc));
+            build_label(ui, "Media source:", format!("{:x}", feedback.media_ssrc));
+            build_label(ui, "Feedback count:", feedback.feedback_count.to_string());
+        });
+        ui.vertical(|ui| {
+            let base = feedback.base_sequence_number;
+            let last = base.wrapping_add(feedback.receive_times.len().saturating_sub(1) as u16);
+            build_label(ui, "Sequence numbers:", format!("{} - {}", base, last));
+            build_label(ui, "Received:", feedback.get_received_count().to_string());
+            build_label(ui, "Lost:", feedback.get_lost_count().to_string());
+        });
+    });
+}
+
 fn build_label(ui: &mut Ui, bold: impl Into<String>, normal: impl Into<String>) {
     let source_label = RichText::new(bold.into()).strong();
     ui.horizontal(|ui| {
@@ -1,5 +1,6 @@
 use super::is_stream_visible;
-use crate::streams::RefStreams;
+use crate::streams::{stream::RtpInfo, RefStreams};
+use crate::utils::ntp_to_string;
 use eframe::epaint::Color32;
 use egui::RichText;
 use egui_extras::{Column, TableBody, TableBuilder};
@@ -62,10 +63,12 @@ impl RtpPacketsTable {
             ("Payload Type", "RTP payload type informs the receiver about the codec or encoding"),
             ("Sequence Number", "RTP sequence number ensures correct order and helps detect packet loss"),
             ("Timestamp", "RTP timestamp is the sender time of generating packet"),
+            ("Sender time", "Sender's wall-clock time of generating packet, mapped from the RTP timestamp using RTCP Sender Reports"),
             ("SSRC", "RTP SSRC (Synchronization Source Identifier) identifies the source of an RTP stream"),
             ("Alias", "Locally assigned SSRC alias to make differentiating streams more convenient"),
             ("CSRC", "RTP CSRC (Contributing Source Identifier)\nSSRC identifiers of the sources that have contributed to a composite RTP packet,\ntypically used for audio mixing in conferences."),
             ("Payload Length", "RTP payload length (Excluding header and extensions)"),
+            ("Payload", "Information decoded from the payload, if its format is known (e.g. from SDP)"),
         ];
         TableBuilder::new(ui)
             .striped(true)
@@ -74,9 +77,12 @@ impl RtpPacketsTable {
             .column(Column::remainder().at_least(40.0))
             .column(Column::remainder().at_least(80.0))
             .columns(Column::remainder().at_least(130.0), 2)
-            .columns(Column::remainder().at_least(80.0), 7)
+            .columns(Column::remainder().at_least(80.0), 6)
+            .column(Column::remainder().at_least(170.0))
+            .column(Column::remainder().at_least(80.0))
             .column(Column::remainder().at_most(50.0))
             .columns(Column::remainder().at_least(80.0), 2)
+            .column(Column::remainder().at_least(160.0))
             .header(30.0, |mut header| {
                 header_labels.iter().for_each(|(label, desc)| {
                     header.col(|ui| {
@@ -174,6 +180,18 @@ impl RtpPacketsTable {
                 ui.label(rtp_packet.timestamp.to_string());
             });
 
+            let rtp_info = streams
+                .streams
+                .get(&key)
+                .and_then(|stream| stream.get_rtp_info(packet.id));
+            row.col(|ui| {
+                let Some(ntp_time) = rtp_info.and_then(|rtp_info| rtp_info.ntp_time) else {
+                    ui.label("N/A");
+                    return;
+                };
+                ui.label(ntp_to_string(ntp_time));
+            });
+
             row.col(|ui| {
                 ui.label(format!("{:x}", rtp_packet.ssrc));
             });
@@ -204,11 +222,50 @@ impl RtpPacketsTable {
             row.col(|ui| {
                 ui.label(rtp_packet.payload_length.to_string());
             });
+
+            let (_, resp) = row.col(|ui| {
+                let Some(rtp_info) = rtp_info else {
+                    return;
+                };
+                let mut labels = Vec::new();
+                if let Some(payload_info) = &rtp_info.payload_info {
+                    labels.push(payload_info.to_string());
+                }
+                if let Some(descriptor) = &rtp_info.dependency_descriptor {
+                    labels.push(descriptor.to_string());
+                }
+                let text = RichText::from(labels.join(", "));
+                if rtp_info.is_keyframe() {
+                    ui.label(text.strong());
+                } else {
+                    ui.label(text);
+                }
+            });
+
+            if let Some(details) = rtp_info.and_then(get_payload_details) {
+                resp.on_hover_text(details);
+            }
         });
     }
 }
 
-fn format_boolean(value: bool) -> RichText {
+fn get_payload_details(rtp_info: &RtpInfo) -> Option<String> {
+    let mut details = String::new();
+    if let Some(payload_info) = &rtp_info.payload_info {
+        details.push_str(&payload_info.get_details());
+    }
+    if let Some(descriptor) = &rtp_info.dependency_descriptor {
+        details.push_str(&descriptor.get_details());
+    }
+
+    if details.is_empty() {
+        None
+    } else {
+        Some(details)
+    }
+}
+
+pub fn format_boolean(value: bool) -> RichText {
     if value {
         RichText::from("✔").color(Color32::GREEN)
     } else {
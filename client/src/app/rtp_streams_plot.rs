@@ -1,9 +1,9 @@
 use self::SettingsXAxis::*;
 use super::is_stream_visible;
+use crate::streams::quality::{QualityWindow, QUALITY_WINDOW};
 use crate::streams::stream::{RtpInfo, Stream};
 use crate::streams::{RefStreams, Streams};
 use eframe::egui;
-use eframe::egui::TextBuffer;
 use eframe::epaint::Color32;
 use egui::plot::{
     Line, LineStyle, MarkerShape, Plot, PlotBounds, PlotPoint, PlotPoints, PlotUi, Points, Text,
@@ -14,7 +14,7 @@ use rtpeeker_common::packet::SessionPacket;
 use rtpeeker_common::rtcp::ReceptionReport;
 use rtpeeker_common::rtp::payload_type::MediaType;
 use rtpeeker_common::StreamKey;
-use rtpeeker_common::{Packet, RtcpPacket, RtpPacket};
+use rtpeeker_common::{Packet, RtcpPacket};
 use std::cell::Ref;
 use std::collections::HashMap;
 use std::fmt::{Display, Error, Formatter};
@@ -26,7 +26,8 @@ struct PointData {
     on_hover: String,
     color: Color32,
     radius: f32,
-    is_rtcp: bool,
+    // RTCP packets, quality scores and events are drawn without the payload length line
+    is_event: bool,
     marker_shape: MarkerShape,
 }
 
@@ -42,6 +43,17 @@ struct StreamText {
     on_hover: String,
 }
 
+// packets shown around the target on each side
+const TARGET_MARGIN: i64 = 50;
+
+// part of a stream to show, e.g. a loss event clicked in the streams table
+pub struct PlotTarget {
+    pub stream_key: StreamKey,
+    // sequence numbers relative to the stream's first packet
+    pub start: i64,
+    pub length: i64,
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 enum SettingsXAxis {
     RtpTimestamp,
@@ -116,6 +128,15 @@ impl RtpStreamsPlot {
         });
     }
 
+    pub fn show_target(&mut self, target: PlotTarget) {
+        self.x_axis = SequenceNumer;
+        self.streams_visibility.insert(target.stream_key, true);
+        self.slider_start = (target.start - TARGET_MARGIN).max(0);
+        self.slider_length = target.length + 2 * TARGET_MARGIN;
+        self.requires_reset = true;
+        self.set_plot_bounds = true;
+    }
+
     fn build_help_section(ui: &mut Ui) {
         ui.horizontal(|ui| {
             ui.vertical(|ui| {
@@ -150,10 +171,22 @@ impl RtpStreamsPlot {
                     ui.label(RichText::from("\t🔴").color(Color32::GOLD));
                     ui.label("At least one previous packet is lost");
                 });
+                ui.horizontal(|ui| {
+                    ui.label(RichText::from("\t◆").color(Color32::LIGHT_BLUE));
+                    ui.label("RTP packet starts a keyframe (if payload format is known)");
+                });
                 ui.horizontal(|ui| {
                     ui.label(RichText::from("\t■").color(Color32::from_rgb(200, 0, 200)));
                     ui.label("RTCP packet");
                 });
+                ui.horizontal(|ui| {
+                    ui.label(RichText::from("\t▲").color(Color32::YELLOW));
+                    ui.label("Estimated MOS of audio in a window (colored from green to red)");
+                });
+                ui.horizontal(|ui| {
+                    ui.label(RichText::from("\t✳").color(Color32::RED));
+                    ui.label("Quality rule event, see the Events tab");
+                });
             })
         });
     }
@@ -198,22 +231,46 @@ impl RtpStreamsPlot {
 
     fn toggle_streams(&mut self, ui: &mut Ui) {
         ui.horizontal_wrapped(|ui| {
-            let mut aliases = Vec::new();
-            let streams = &self.streams.borrow().streams;
-            let keys: Vec<_> = streams.keys().collect();
-
-            keys.iter().for_each(|&key| {
-                let alias = streams.get(key).unwrap().alias.to_string();
-                aliases.push((*key, alias));
-            });
-            aliases.sort_by(|(_, a), (_, b)| a.cmp(b));
+            let streams = self.streams.borrow();
+            let groups = streams.get_groups();
+            let get_label = |key: &StreamKey| {
+                let stream = streams.streams.get(key).unwrap();
+                match stream.get_role() {
+                    Some(role) => format!("{} ({})", stream.alias, role),
+                    None => stream.alias.to_string(),
+                }
+            };
 
             ui.label(RichText::from("Toggle streams: ").strong());
-            aliases.iter().for_each(|(key, alias)| {
-                let selected = is_stream_visible(&mut self.streams_visibility, *key);
-                if ui.checkbox(selected, alias).clicked() {
+            groups.iter().for_each(|group| {
+                if group.keys.len() == 1 && group.mid.is_none() {
+                    let key = group.keys[0];
+                    let selected = is_stream_visible(&mut self.streams_visibility, key);
+                    if ui.checkbox(selected, get_label(&key)).clicked() {
+                        self.requires_reset = true
+                    }
+                    return;
+                }
+
+                // group checkbox toggles all of its streams
+                let mut all_selected = group
+                    .keys
+                    .iter()
+                    .all(|key| *is_stream_visible(&mut self.streams_visibility, *key));
+                if ui.checkbox(&mut all_selected, "").clicked() {
+                    group.keys.iter().for_each(|key| {
+                        self.streams_visibility.insert(*key, all_selected);
+                    });
                     self.requires_reset = true
                 }
+                ui.collapsing(&group.name, |ui| {
+                    group.keys.iter().for_each(|key| {
+                        let selected = is_stream_visible(&mut self.streams_visibility, *key);
+                        if ui.checkbox(selected, get_label(key)).clicked() {
+                            self.requires_reset = true
+                        }
+                    });
+                });
             });
         });
     }
@@ -285,7 +342,7 @@ impl RtpStreamsPlot {
                 on_hover,
                 color,
                 radius,
-                is_rtcp,
+                is_event,
                 marker_shape,
             } = point_data;
             if *y_top > heighest_y {
@@ -299,12 +356,12 @@ impl RtpStreamsPlot {
                 .shape(*marker_shape);
 
             plot_ui.points(point);
-            if !is_rtcp {
+            if !is_event {
                 plot_ui.line(
                     Line::new(PlotPoints::new(vec![[*x, *y_low], [*x, *y_top]]))
                         .color(Color32::RED)
                         .highlight(false)
-                        .width(0.5),
+                        .width(0.5_f32),
                 );
             }
         }
@@ -314,7 +371,7 @@ impl RtpStreamsPlot {
                 Line::new(PlotPoints::new(vec![[*x_start, *y], [*x_end, *y]]))
                     .color(Color32::GRAY)
                     .style(LineStyle::Solid)
-                    .width(0.5),
+                    .width(0.5_f32),
             );
         }
 
@@ -375,8 +432,8 @@ impl RtpStreamsPlot {
                         rtp_packet.time.as_secs_f64() - first_packet.timestamp.as_secs_f64()
                     }
                     SequenceNumer => {
-                        rtp_packet.packet.sequence_number as f64
-                            - first_rtp_packet.sequence_number as f64
+                        rtp_packet.extended_sequence_number as f64
+                            - first_rtp_id.extended_sequence_number as f64
                     }
                 };
                 if stream_separator_length < max_x {
@@ -385,9 +442,14 @@ impl RtpStreamsPlot {
             }
         });
 
-        streams
-            .streams
+        // streams of a group are drawn next to each other
+        let keys: Vec<_> = streams
+            .get_groups()
             .iter()
+            .flat_map(|group| group.keys.iter().copied())
+            .collect();
+        keys.iter()
+            .map(|key| (key, streams.streams.get(key).unwrap()))
             .enumerate()
             .for_each(|(i, (key, stream))| {
                 if !*(is_stream_visible(&mut self.streams_visibility, *key)) {
@@ -416,7 +478,7 @@ impl RtpStreamsPlot {
                 self.stream_texts.push(StreamText {
                     x: 0.0,
                     y: this_stream_y_baseline,
-                    on_hover: String::from(&format!("{} ({:x})  ", stream.alias, stream.ssrc)),
+                    on_hover: build_stream_label(stream),
                 });
                 if let Some((_, _)) = points_x_and_y_top.last() {
                     self.stream_separator_lines.push(StreamSeperatorLine {
@@ -441,6 +503,18 @@ impl RtpStreamsPlot {
     }
 }
 
+fn build_stream_label(stream: &Stream) -> String {
+    let mut label = format!("{} ({:x})", stream.alias, stream.ssrc);
+    if let Some(mid) = &stream.mid {
+        label.push_str(&format!(" MID {}", mid));
+    }
+    if let Some(role) = stream.get_role() {
+        label.push_str(&format!(" {}", role));
+    }
+    label.push_str("  ");
+    label
+}
+
 fn get_highest_y(
     streams: &Ref<Streams>,
     points_x_and_y_top: &mut Vec<(f64, f64)>,
@@ -456,9 +530,6 @@ fn get_highest_y(
 
     let first_rtp_id = rtp_packets.first().unwrap();
     let first_packet = streams.packets.get(first_rtp_id.id).unwrap();
-    let SessionPacket::Rtp(ref first_rtp_packet) = first_packet.contents else {
-        unreachable!();
-    };
 
     rtp_packets
         .iter()
@@ -473,7 +544,7 @@ fn get_highest_y(
 
             let (_, _, y_top) = get_x_and_y(
                 points_x_and_y_top,
-                first_rtp_packet,
+                first_rtp_id,
                 previous_packet,
                 packet,
                 settings_x_axis,
@@ -508,9 +579,6 @@ fn build_stream_points(
 
     let first_rtp_id = rtp_packets.first().unwrap();
     let first_packet = streams.packets.get(first_rtp_id.id).unwrap();
-    let SessionPacket::Rtp(ref first_rtp_packet) = first_packet.contents else {
-        unreachable!();
-    };
 
     let mut on_hover = String::new();
     let mut maybe_prev_id = None;
@@ -553,6 +621,17 @@ fn build_stream_points(
                 }
                 on_hover.push_str("------------------------\n");
             }
+            RtcpPacket::TransportFeedback(feedback) => {
+                on_hover.push_str("Transport-wide CC Feedback\n\n");
+                on_hover.push_str(&format!("Source: {:x}\n", feedback.sender_ssrc));
+                on_hover.push_str(&format!(
+                    "Base sequence number: {}\n",
+                    feedback.base_sequence_number
+                ));
+                on_hover.push_str(&format!("Received: {}\n", feedback.get_received_count()));
+                on_hover.push_str(&format!("Lost: {}\n", feedback.get_lost_count()));
+                on_hover.push_str("------------------------\n");
+            }
             RtcpPacket::ApplicationDefined => {
                 on_hover.push_str("\nGoodbye\n");
                 on_hover.push_str("------------------------\n");
@@ -604,7 +683,7 @@ fn build_stream_points(
                             on_hover: saved_on_hover,
                             color: Color32::from_rgb(200, 0, 200),
                             radius: 3.5,
-                            is_rtcp: true,
+                            is_event: true,
                             marker_shape: MarkerShape::Square,
                         });
                         on_hover = String::new();
@@ -620,6 +699,32 @@ fn build_stream_points(
         }
         maybe_prev_id = Some(rtcp_info.id);
     });
+    if settings_x_axis == RawTimestamp {
+        for window in stream.get_quality_history() {
+            points_data.push(PointData {
+                x: window.start + QUALITY_WINDOW / 2.0,
+                y_low: this_stream_y_baseline - 5.0,
+                y_top: this_stream_y_baseline - 5.0,
+                on_hover: build_quality_on_hover_text(window),
+                color: get_mos_color(window.score.mos),
+                radius: 4.0,
+                is_event: true,
+                marker_shape: MarkerShape::Up,
+            });
+        }
+        for event in &stream.events {
+            points_data.push(PointData {
+                x: event.time.as_secs_f64() - first_packet.timestamp.as_secs_f64(),
+                y_low: this_stream_y_baseline - 10.0,
+                y_top: this_stream_y_baseline - 10.0,
+                on_hover: format!("{}\n{}", event.rule, event.description),
+                color: Color32::RED,
+                radius: 5.0,
+                is_event: true,
+                marker_shape: MarkerShape::Asterisk,
+            });
+        }
+    }
     rtp_packets
         .iter()
         .enumerate()
@@ -633,7 +738,7 @@ fn build_stream_points(
 
             let (x, y_low, y_top) = get_x_and_y(
                 points_x_and_y_top,
-                first_rtp_packet,
+                first_rtp_id,
                 previous_packet,
                 packet,
                 settings_x_axis,
@@ -649,8 +754,8 @@ fn build_stream_points(
                 on_hover,
                 color: get_color(packet),
                 radius: get_radius(packet),
-                is_rtcp: false,
-                marker_shape: MarkerShape::Circle,
+                is_event: false,
+                marker_shape: get_marker_shape(packet),
             });
 
             if *previous_stream_max_y < y_top {
@@ -665,6 +770,34 @@ fn build_stream_points(
         });
 }
 
+fn build_quality_on_hover_text(window: &QualityWindow) -> String {
+    let score = &window.score;
+    format!(
+        "{:.0}-{:.0} s\nMOS: {:.2}\nR-factor: {:.1}\n\
+         Loss: {:.2}%\nBurst ratio: {:.2}\nJitter: {:.3} ms",
+        window.start,
+        window.start + QUALITY_WINDOW,
+        score.mos,
+        score.r_factor,
+        score.loss * 100.0,
+        score.burst_ratio,
+        score.jitter * 1000.0
+    )
+}
+
+// quality categories, see ITU-T G.109
+fn get_mos_color(mos: f64) -> Color32 {
+    if mos >= 4.03 {
+        Color32::GREEN
+    } else if mos >= 3.6 {
+        Color32::YELLOW
+    } else if mos >= 3.1 {
+        Color32::from_rgb(255, 140, 0)
+    } else {
+        Color32::RED
+    }
+}
+
 fn build_reception_report(on_hover: &mut String, report: &&ReceptionReport) {
     on_hover.push_str(&"-".repeat(160));
     on_hover.push('\n');
@@ -686,7 +819,7 @@ fn build_reception_report(on_hover: &mut String, report: &&ReceptionReport) {
 #[allow(clippy::too_many_arguments)]
 fn get_x_and_y(
     points_x_and_y_top: &mut [(f64, f64)],
-    first_rtp_packet: &RtpPacket,
+    first_rtp: &RtpInfo,
     previous_packet: Option<&Packet>,
     rtp: &RtpInfo,
     settings_x_axis: SettingsXAxis,
@@ -712,13 +845,13 @@ fn get_x_and_y(
                 };
 
                 (
-                    rtp.packet.timestamp as f64 - first_rtp_packet.timestamp as f64,
+                    rtp.packet.timestamp as f64 - first_rtp.packet.timestamp as f64,
                     last_y_top,
                     last_y_top + height,
                 )
             } else {
                 (
-                    rtp.packet.timestamp as f64 - first_rtp_packet.timestamp as f64,
+                    rtp.packet.timestamp as f64 - first_rtp.packet.timestamp as f64,
                     this_stream_y_baseline,
                     this_stream_y_baseline + height,
                 )
@@ -730,7 +863,7 @@ fn get_x_and_y(
             this_stream_y_baseline + height,
         ),
         SequenceNumer => (
-            (rtp.packet.sequence_number - first_rtp_packet.sequence_number) as f64,
+            rtp.extended_sequence_number as f64 - first_rtp.extended_sequence_number as f64,
             this_stream_y_baseline,
             this_stream_y_baseline + height,
         ),
@@ -772,9 +905,14 @@ fn build_on_hover_text(
             }
         }
     } else {
-        "".as_str()
+        ""
     };
     on_hover.push_str(marker_info);
+    if rtp.talkspurt_start {
+        on_hover.push_str("\nFirst packet of a talkspurt.\n");
+    } else if rtp.is_comfort_noise() {
+        on_hover.push_str("\nComfort noise, the sender is silent.\n");
+    }
     on_hover.push('\n');
     on_hover.push_str(&format!("Sequence number: {}", rtp.packet.sequence_number));
     on_hover.push('\n');
@@ -790,6 +928,14 @@ fn build_on_hover_text(
     on_hover.push('\n');
     on_hover.push_str(&rtp.packet.payload_type.to_string());
     on_hover.push('\n');
+    if let Some(payload_info) = &rtp.payload_info {
+        on_hover.push_str(&payload_info.to_string());
+        on_hover.push('\n');
+    }
+    if let Some(descriptor) = &rtp.dependency_descriptor {
+        on_hover.push_str(&descriptor.to_string());
+        on_hover.push('\n');
+    }
     let str = match settings_x_axis {
         RtpTimestamp => format!("x = {} [RTP timestamp]\n", x),
         RawTimestamp => format!("x = {:.5} [s]\n", x),
@@ -799,8 +945,16 @@ fn build_on_hover_text(
     on_hover
 }
 
+fn get_marker_shape(rtp: &RtpInfo) -> MarkerShape {
+    if rtp.is_keyframe() {
+        MarkerShape::Diamond
+    } else {
+        MarkerShape::Circle
+    }
+}
+
 fn get_radius(rtp: &RtpInfo) -> f32 {
-    if rtp.prev_lost {
+    if rtp.prev_lost || rtp.is_keyframe() {
         3.5
     } else if rtp.packet.marker {
         2.5
@@ -812,6 +966,8 @@ fn get_radius(rtp: &RtpInfo) -> f32 {
 fn get_color(rtp: &RtpInfo) -> Color32 {
     if rtp.prev_lost {
         Color32::GOLD
+    } else if rtp.is_keyframe() {
+        Color32::LIGHT_BLUE
     } else if rtp.packet.marker {
         Color32::GREEN
     } else {
@@ -1,9 +1,19 @@
-use crate::streams::{stream::Stream, RefStreams};
+use super::rtp_streams_plot::PlotTarget;
+use crate::streams::quality::QUALITY_WINDOW;
+use crate::streams::recovery::{RecoveryStats, RetransmissionStats};
+use crate::streams::stream::{MpegTsStats, ReceptionComparison, RttSample, Stream};
+use crate::streams::{RefStreams, StreamGroup};
+use eframe::epaint::Color32;
 use egui::plot::{Line, Plot, PlotPoints};
-use egui::{TextEdit, Vec2};
+use egui::{RichText, TextEdit, Vec2};
 use egui_extras::{Column, TableBody, TableBuilder};
 use ewebsock::{WsMessage, WsSender};
+use rtpeeker_common::packet::TransportProtocol;
+use rtpeeker_common::rtp::mpeg_ts::stream_type_name;
+use rtpeeker_common::rtp::telephone_event::event_name;
 use rtpeeker_common::{Request, StreamKey};
+use std::collections::HashSet;
+use std::net::SocketAddr;
 
 const SDP_PROMPT: &str = "Paste your SDP media section here, e.g.
 m=audio 5004 RTP/AVP 96
@@ -12,12 +22,24 @@ a=rtpmap:96 L24/48000/2
 a=recvonly
 ";
 
+const GROUP_ROW_HEIGHT: f32 = 30.0;
+const STREAM_ROW_HEIGHT: f32 = 100.0;
+
+type GroupId = ((SocketAddr, SocketAddr, TransportProtocol), String);
+
+enum StreamsTableRow<'a> {
+    Group(&'a StreamGroup),
+    Stream(StreamKey),
+}
+
 pub struct RtpStreamsTable {
     streams: RefStreams,
     ws_sender: WsSender,
     sdp_window_open: bool,
     chosen_key: Option<StreamKey>,
     sdp: String,
+    collapsed_groups: HashSet<GroupId>,
+    plot_target: Option<PlotTarget>,
 }
 
 impl RtpStreamsTable {
@@ -28,14 +50,18 @@ impl RtpStreamsTable {
             sdp_window_open: false,
             chosen_key: None,
             sdp: String::new(),
+            collapsed_groups: HashSet::new(),
+            plot_target: None,
         }
     }
 
-    pub fn ui(&mut self, ctx: &egui::Context) {
+    // returns the plot target, if a loss event was clicked
+    pub fn ui(&mut self, ctx: &egui::Context) -> Option<PlotTarget> {
         egui::CentralPanel::default().show(ctx, |ui| {
             self.build_table(ui);
         });
         self.build_sdp_window(ctx);
+        self.plot_target.take()
     }
 
     fn build_sdp_window(&mut self, ctx: &egui::Context) {
@@ -76,13 +102,25 @@ impl RtpStreamsTable {
             ("Destination", "Destination IP address and port"),
             ("CNAME", "Source Description CNAME value, if received (latest one if changed mid-stream"),
             ("Payload type", "Payload type of this stream (latest one if changed mid-stream)"),
-            ("Packet count", "Number of packets in stream"),
-            ("Packet loss", "Percentage of packets lost"),
+            ("Packet count", "Number of packets in stream and confidence that they form a RTP stream"),
+            ("Packet loss", "Percentage of packets lost (and lost after retransmission, if RTX stream is associated), and the number of duplicate, reordered and late packets"),
+            ("Burst loss", "Loss density within bursts and gaps (RFC 3611), click to list the loss events"),
             ("Duration", "Difference between last timestamp and first timestamp."),
             ("Mean jitter", "Average of jitter for all of the packets"),
+            ("Reported vs observed", "Loss fraction and jitter from the latest RTCP reception report compared with the ones observed at the capture point in the same interval, divergence is highlighted, hover to see the details"),
+            ("RTT", "Mean round-trip time from RTCP reception reports (LSR and DLSR) as computed by the sender, or between the capture point and the receiver, hover to see min and max"),
             ("Mean bitrate", "Sum of packet sizes (IP header included) divided by stream's duration"),
             ("Mean RTP bitrate", "Sum of packet sizes (RTP only) divided by stream's duration"),
             ("Mean packet rate", "Number of packets divided by stream's duration in seconds"),
+            ("Clock drift", "Deviation of the sender's RTP clock rate estimated from RTCP Sender Reports from the nominal one, in ppm"),
+            ("Layers", "Spatial and temporal layers (VP8/VP9), hover to see bitrate and frame rate of each layer"),
+            ("Frames", "Video frame rate and frames incomplete due to loss (packets grouped by RTP timestamp and marker bit), hover to see frame sizes, frame jitter and keyframe interval"),
+            ("Payload summary", "Codec-specific statistics, e.g. Opus packetization time and DTX ratio or MPEG-TS errors"),
+            ("Speech activity", "Share of the stream's duration with speech and number of talkspurts (audio with silence suppression)"),
+            ("MOS", "Estimated MOS and R-factor (ITU-T G.107 E-model) of audio streams from the codec, loss and its burstiness, jitter and RTT, hover to see the worst window"),
+            ("Recovery", "Lost packets that could be recovered with FEC or RED redundancy present in the capture"),
+            ("DTMF", "Digits sent as RFC 4733 telephone events, hover to see their timing"),
+            ("RTT history", "Plot representing round-trip time from all of the stream's reception reports"),
             ("Jitter history", "Plot representing jitter for all of the stream's packets")
         ];
         TableBuilder::new(ui)
@@ -93,9 +131,16 @@ impl RtpStreamsTable {
             .column(Column::initial(80.0).at_least(80.0))
             .columns(Column::initial(140.0).at_least(140.0), 2)
             .columns(Column::initial(80.0).at_least(80.0), 3)
+            .column(Column::initial(130.0).at_least(130.0))
+            .column(Column::initial(110.0).at_least(110.0))
             .column(Column::initial(70.0).at_least(70.0))
-            .column(Column::initial(70.0).at_least(70.0))
+            .column(Column::initial(80.0).at_least(80.0))
+            .column(Column::initial(150.0).at_least(150.0))
+            .columns(Column::initial(80.0).at_least(80.0), 6)
+            .column(Column::initial(110.0).at_least(110.0))
+            .column(Column::initial(120.0).at_least(120.0))
             .columns(Column::initial(80.0).at_least(80.0), 4)
+            .column(Column::initial(250.0).at_least(250.0))
             .column(Column::remainder().at_least(380.0).resizable(false))
             .header(30.0, |mut header| {
                 header_labels.iter().for_each(|(label, desc)| {
@@ -111,16 +156,85 @@ impl RtpStreamsTable {
     }
 
     fn build_table_body(&mut self, body: TableBody) {
+        let mut requests = Vec::new();
+        let mut is_alias_changed = false;
         let mut streams = self.streams.borrow_mut();
-        let keys: Vec<_> = streams.streams.keys().cloned().collect();
+        // the streams are mutated while the rows are built
+        let groups = streams.get_groups().to_vec();
+
+        let mut rows = Vec::new();
+        for group in &groups {
+            // single stream without MID does not need a group row
+            if group.keys.len() == 1 && group.mid.is_none() {
+                rows.push(StreamsTableRow::Stream(group.keys[0]));
+                continue;
+            }
+
+            rows.push(StreamsTableRow::Group(group));
+            if !self.is_group_collapsed(group) {
+                rows.extend(group.keys.iter().map(|key| StreamsTableRow::Stream(*key)));
+            }
+        }
+        let heights = rows.iter().map(|row| match row {
+            StreamsTableRow::Group(_) => GROUP_ROW_HEIGHT,
+            StreamsTableRow::Stream(_) => STREAM_ROW_HEIGHT,
+        });
 
-        body.rows(100.0, streams.streams.len(), |id, mut row| {
-            let key = keys.get(id).unwrap();
+        body.heterogeneous_rows(heights, |id, mut row| {
+            let key = match &rows[id] {
+                StreamsTableRow::Group(group) => {
+                    let group_id = (group.transport, group.name.clone());
+                    let is_collapsed = self.collapsed_groups.contains(&group_id);
+                    row.col(|ui| {
+                        let icon = if is_collapsed { "⏵" } else { "⏷" };
+                        if ui.button(icon).clicked() {
+                            if is_collapsed {
+                                self.collapsed_groups.remove(&group_id);
+                            } else {
+                                self.collapsed_groups.insert(group_id);
+                            }
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(&group.name).strong());
+                    });
+                    row.col(|ui| {
+                        ui.label(group.transport.0.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(group.transport.1.to_string());
+                    });
+                    row.col(|ui| {
+                        let roles = group
+                            .keys
+                            .iter()
+                            .map(|key| {
+                                let stream = streams.streams.get(key).unwrap();
+                                stream.get_role().unwrap_or_else(|| stream.alias.clone())
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        ui.label(format!("{} streams: {}", group.keys.len(), roles));
+                    });
+                    return;
+                }
+                StreamsTableRow::Stream(key) => key,
+            };
+            let has_sdp = streams.has_sdp(key);
+            let recovery_stats = streams.get_recovery_stats(key).cloned();
+            let retransmission_stats = streams.get_retransmission_stats(key).cloned();
+            let rtx_primary = streams
+                .get_rtx_primary_key(key)
+                .and_then(|primary_key| streams.streams.get(&primary_key))
+                .map(|primary| (primary.alias.clone(), primary.ssrc));
             let stream = streams.streams.get_mut(key).unwrap();
 
             row.col(|ui| {
                 let text_edit = TextEdit::singleline(&mut stream.alias).frame(false);
-                ui.add(text_edit);
+                is_alias_changed |= ui.add(text_edit).changed();
+                if let Some(role) = stream.get_role() {
+                    ui.label(RichText::new(role).weak());
+                }
             });
 
             row.col(|ui| {
@@ -153,11 +267,19 @@ impl RtpStreamsTable {
             });
             row.col(|ui| {
                 ui.label(stream.rtp_packets.len().to_string());
+                let confidence = stream.get_rtp_confidence() * 100.0;
+                ui.label(RichText::new(format!("{:.0}% RTP", confidence)).weak())
+                    .on_hover_text(
+                        "Share of packets with sequence number and timestamp consistent with the previous packet",
+                    );
             });
             row.col(|ui| {
-                let lost = stream.get_expected_count() - stream.rtp_packets.len();
-                let lost_fraction = lost as f64 / stream.get_expected_count() as f64;
-                ui.label(format!("{:.3}%", lost_fraction * 100.0));
+                build_packet_loss_label(ui, stream, &retransmission_stats, &rtx_primary);
+            });
+            row.col(|ui| {
+                if let Some(target) = build_burst_loss_label(ui, stream, key) {
+                    self.plot_target = Some(target);
+                }
             });
             row.col(|ui| {
                 let duration = stream.get_duration().as_secs_f64();
@@ -169,6 +291,13 @@ impl RtpStreamsTable {
                     None => "N/A".to_string(),
                 };
                 ui.label(jitter_label);
+                build_inferred_clock_rate_label(ui, stream);
+            });
+            row.col(|ui| {
+                build_reception_comparison_label(ui, stream);
+            });
+            row.col(|ui| {
+                build_rtt_label(ui, stream);
             });
             row.col(|ui| {
                 let bitrate = stream.get_mean_bitrate() / 1000.0;
@@ -182,6 +311,33 @@ impl RtpStreamsTable {
                 let packet_rate = stream.get_mean_packet_rate();
                 ui.label(format!("{:.1} /s", packet_rate));
             });
+            row.col(|ui| {
+                build_clock_drift_label(ui, stream);
+            });
+            row.col(|ui| {
+                build_layers_label(ui, stream);
+            });
+            row.col(|ui| {
+                build_frames_label(ui, stream);
+            });
+            row.col(|ui| {
+                build_payload_summary(ui, stream);
+            });
+            row.col(|ui| {
+                build_speech_activity_label(ui, stream);
+            });
+            row.col(|ui| {
+                build_quality_label(ui, stream);
+            });
+            row.col(|ui| {
+                build_recovery_label(ui, &recovery_stats);
+            });
+            row.col(|ui| {
+                build_dtmf_label(ui, stream);
+            });
+            row.col(|ui| {
+                build_rtt_plot(ui, stream);
+            });
             let (_, resp) = row.col(|ui| {
                 build_jitter_plot(ui, stream);
             });
@@ -193,13 +349,35 @@ impl RtpStreamsTable {
                     self.sdp = String::new();
                     self.sdp_window_open = true;
                 }
+                if has_sdp && ui.button("Remove SDP").clicked() {
+                    ui.close_menu();
+                    requests.push(Request::RemoveSdp(*key));
+                }
             });
         });
+
+        // cached stats refer to the streams by their aliases
+        if is_alias_changed {
+            streams.invalidate_cache();
+        }
+
+        // cannot take mutable reference to self
+        // unless `streams` is dropped, hence the `requests` vector
+        std::mem::drop(streams);
+        requests.into_iter().for_each(|req| self.send_request(req));
+    }
+
+    fn is_group_collapsed(&self, group: &StreamGroup) -> bool {
+        self.collapsed_groups
+            .contains(&(group.transport, group.name.clone()))
     }
 
     fn send_sdp_request(&mut self) {
         let request = Request::ParseSdp(self.chosen_key.unwrap(), self.sdp.clone());
+        self.send_request(request);
+    }
 
+    fn send_request(&mut self, request: Request) {
         let Ok(msg) = request.encode() else {
             log::error!("Failed to encode a request message");
             return;
@@ -210,6 +388,558 @@ impl RtpStreamsTable {
     }
 }
 
+fn build_packet_loss_label(
+    ui: &mut egui::Ui,
+    stream: &Stream,
+    retransmission_stats: &Option<RetransmissionStats>,
+    rtx_primary: &Option<(String, u32)>,
+) {
+    let lost = stream.get_lost_count();
+    let lost_fraction = lost as f64 / stream.get_expected_count() as f64;
+    let sequence_hover = format!(
+        "Expected packets: {}\nLost packets: {}\nDuplicate packets: {}\nReordered packets: {}\nLate packets: {}",
+        stream.get_expected_count(),
+        lost,
+        stream.duplicate_packets,
+        stream.reordered_packets,
+        stream.late_packets
+    );
+
+    let (label, on_hover) = if let Some((alias, ssrc)) = rtx_primary {
+        (
+            format!("{:.3}%\nRTX of {}", lost_fraction * 100.0, alias),
+            format!(
+                "Retransmissions of stream {} ({:x})\n{}",
+                alias, ssrc, sequence_hover
+            ),
+        )
+    } else if let Some(stats) = retransmission_stats {
+        let lost_after_fraction =
+            stats.lost_after_retransmission as f64 / stream.get_expected_count() as f64;
+        (
+            format!(
+                "{:.3}%\n{:.3}% after RTX",
+                lost_fraction * 100.0,
+                lost_after_fraction * 100.0
+            ),
+            format!(
+                "{}\nRTX stream: {} ({:x})\nRetransmitted packets: {}\nLost after retransmission: {}",
+                sequence_hover,
+                stats.rtx_alias,
+                stats.rtx_ssrc,
+                stats.retransmitted,
+                stats.lost_after_retransmission
+            ),
+        )
+    } else {
+        (format!("{:.3}%", lost_fraction * 100.0), sequence_hover)
+    };
+
+    ui.label(label).on_hover_text(on_hover);
+    let counts = format!(
+        "{} dup, {} reord, {} late",
+        stream.duplicate_packets, stream.reordered_packets, stream.late_packets
+    );
+    ui.label(RichText::new(counts).weak())
+        .on_hover_text("Duplicate, reordered and late packets");
+}
+
+// returns the plot target if one of the loss events was clicked
+fn build_burst_loss_label(
+    ui: &mut egui::Ui,
+    stream: &Stream,
+    key: &StreamKey,
+) -> Option<PlotTarget> {
+    let stats = stream.get_burst_stats();
+    if stats.events.is_empty() {
+        ui.label("No loss");
+        return None;
+    }
+
+    let on_hover = format!(
+        "Bursts: {}\nBurst density: {:.2}%\nGap density: {:.2}%\nMean burst length: {:.1} packets\nLongest loss run: {} packets",
+        stats.bursts,
+        stats.burst_density * 100.0,
+        stats.gap_density * 100.0,
+        stats.mean_burst_length,
+        stats.longest_loss_run
+    );
+    ui.label(format!(
+        "burst {:.1}%\ngap {:.1}%",
+        stats.burst_density * 100.0,
+        stats.gap_density * 100.0
+    ))
+    .on_hover_text(on_hover);
+
+    let first = stream.rtp_packets.first().unwrap();
+    let mut target = None;
+    ui.menu_button(format!("{} events", stats.events.len()), |ui| {
+        egui::ScrollArea::vertical()
+            .max_height(300.0)
+            .show(ui, |ui| {
+                for event in &stats.events {
+                    let last = event.first_sequence_number + event.length as u32 - 1;
+                    let time = event.time.saturating_sub(first.time).as_secs_f64();
+                    let label = format!(
+                        "{}-{}: {} lost at {:.3} s",
+                        event.first_sequence_number as u16, last as u16, event.length, time
+                    );
+                    if ui.button(label).on_hover_text("Show in the plot").clicked() {
+                        ui.close_menu();
+                        target = Some(PlotTarget {
+                            stream_key: *key,
+                            start: event.first_sequence_number as i64
+                                - first.extended_sequence_number as i64,
+                            length: event.length as i64,
+                        });
+                    }
+                }
+            });
+    });
+
+    target
+}
+
+fn build_reception_comparison_label(ui: &mut egui::Ui, stream: &Stream) {
+    let Some(comparison) = stream.reception_comparisons.last() else {
+        ui.label("N/A");
+        return;
+    };
+
+    let highlight = |text: String, is_divergent: bool| {
+        let text = RichText::new(text);
+        if is_divergent {
+            text.color(Color32::RED)
+        } else {
+            text
+        }
+    };
+    let loss = format!(
+        "loss {:.1}% / {:.1}%",
+        comparison.reported_fraction_lost * 100.0,
+        comparison.observed_fraction_lost * 100.0
+    );
+    let jitter = match (comparison.reported_jitter, comparison.observed_jitter) {
+        (Some(reported), Some(observed)) => format!(
+            "jitter {:.1} / {:.1} ms",
+            reported * 1000.0,
+            observed * 1000.0
+        ),
+        _ => "jitter N/A".to_string(),
+    };
+
+    let divergent_count = stream
+        .reception_comparisons
+        .iter()
+        .filter(|comparison| comparison.is_loss_divergent() || comparison.is_jitter_divergent())
+        .count();
+    let on_hover = format!(
+        "Reported by {:x} at {:.2} s (reported / observed)\n\
+         Fraction lost: {:.2}% / {:.2}%\n\
+         Total lost: {} / {}\n\
+         {}\n\
+         {}\n\
+         Divergent reports: {} of {}",
+        comparison.reporter_ssrc,
+        comparison
+            .time
+            .saturating_sub(stream.get_first_time())
+            .as_secs_f64(),
+        comparison.reported_fraction_lost * 100.0,
+        comparison.observed_fraction_lost * 100.0,
+        comparison.reported_total_lost,
+        comparison.observed_total_lost,
+        jitter,
+        get_loss_location(comparison),
+        divergent_count,
+        stream.reception_comparisons.len()
+    );
+
+    ui.vertical(|ui| {
+        ui.label(highlight(loss, comparison.is_loss_divergent()))
+            .on_hover_text(on_hover.as_str());
+        ui.label(highlight(jitter, comparison.is_jitter_divergent()))
+            .on_hover_text(on_hover.as_str());
+    });
+}
+
+fn get_loss_location(comparison: &ReceptionComparison) -> &'static str {
+    let reported = comparison.reported_fraction_lost;
+    let observed = comparison.observed_fraction_lost;
+    if !comparison.is_loss_divergent() {
+        if observed > 0.0 {
+            return "Loss happens before the capture point";
+        }
+        return "No significant loss after the capture point";
+    }
+
+    if reported > observed {
+        "Loss happens after the capture point"
+    } else {
+        "Loss observed exceeds the reported one, packets might have been recovered by the receiver"
+    }
+}
+
+fn build_inferred_clock_rate_label(ui: &mut egui::Ui, stream: &Stream) {
+    let Some(clock_rate) = stream
+        .inferred_clock_rate
+        .filter(|_| stream.is_clock_rate_inferred())
+    else {
+        return;
+    };
+
+    ui.label(RichText::new(format!("~{} Hz", clock_rate)).weak())
+        .on_hover_text(
+            "Clock rate is not known from the payload type or SDP, so it was estimated \
+             from RTP timestamps and arrival times",
+        );
+}
+
+fn build_rtt_label(ui: &mut egui::Ui, stream: &Stream) {
+    let sender_stats = stream.get_sender_rtt_stats();
+    let capture_stats = stream.get_capture_rtt_stats();
+    let Some(stats) = sender_stats.or(capture_stats) else {
+        ui.label("N/A");
+        return;
+    };
+
+    let mut on_hover = String::new();
+    for (name, stats) in [("Sender", sender_stats), ("Capture point", capture_stats)] {
+        let Some(stats) = stats else {
+            continue;
+        };
+        on_hover.push_str(&format!(
+            "{} RTT\nmin: {:.1} ms\navg: {:.1} ms\nmax: {:.1} ms\n",
+            name,
+            stats.min * 1000.0,
+            stats.mean * 1000.0,
+            stats.max * 1000.0
+        ));
+    }
+
+    let source = if sender_stats.is_some() {
+        "sender"
+    } else {
+        "capture point"
+    };
+    ui.label(format!("{:.1} ms\n{}", stats.mean * 1000.0, source))
+        .on_hover_text(on_hover.trim_end());
+}
+
+fn build_clock_drift_label(ui: &mut egui::Ui, stream: &Stream) {
+    let (Some(drift), Some(clock_rate)) = (stream.get_clock_drift(), stream.estimated_clock_rate)
+    else {
+        ui.label("N/A");
+        return;
+    };
+
+    let mut on_hover = format!("Estimated clock rate: {:.1} Hz", clock_rate);
+    if let Some(nominal_clock_rate) = stream.get_nominal_clock_rate() {
+        on_hover.push_str(&format!("\nNominal clock rate: {} Hz", nominal_clock_rate));
+    }
+
+    ui.label(format!("{:+.1} ppm", drift))
+        .on_hover_text(on_hover);
+}
+
+fn build_layers_label(ui: &mut egui::Ui, stream: &Stream) {
+    if stream.layers.is_empty() {
+        ui.label("N/A");
+        return;
+    }
+
+    let layers = stream
+        .layers
+        .keys()
+        .map(|layer| layer.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let on_hover = stream
+        .layers
+        .iter()
+        .map(|(layer, stats)| {
+            format!(
+                "{}: {:.2} kbps, {:.1} fps",
+                layer,
+                stream.get_layer_bitrate(stats) / 1000.0,
+                stream.get_layer_frame_rate(stats)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ui.label(layers).on_hover_text(on_hover);
+}
+
+fn build_frames_label(ui: &mut egui::Ui, stream: &Stream) {
+    let Some(stats) = stream.get_frame_stats() else {
+        ui.label("N/A");
+        return;
+    };
+
+    let jitter = match (
+        stats.frames.last().and_then(|frame| frame.jitter),
+        stats.max_jitter,
+    ) {
+        (Some(jitter), Some(max_jitter)) => format!(
+            "{:.3} ms (max {:.3} ms)",
+            jitter * 1000.0,
+            max_jitter * 1000.0
+        ),
+        _ => "N/A".to_string(),
+    };
+    let keyframe_interval = match stats.keyframe_interval {
+        Some(interval) => format!("{:.2} s", interval),
+        None => "N/A".to_string(),
+    };
+    let on_hover = format!(
+        "Frames: {}\nIncomplete frames: {}\n\
+         Frame size: min {} B, median {} B, 95th percentile {} B, max {} B, mean {:.0} B\n\
+         Frame jitter: {}\nKeyframes: {}\nKeyframe interval: {}",
+        stats.frames.len(),
+        stats.incomplete_frames,
+        stats.get_frame_size(0.0),
+        stats.get_frame_size(0.5),
+        stats.get_frame_size(0.95),
+        stats.get_frame_size(1.0),
+        stats.get_mean_frame_size(),
+        jitter,
+        stats.keyframes,
+        keyframe_interval
+    );
+
+    ui.label(format!(
+        "{:.1} fps\n{} incomplete",
+        stats.frame_rate, stats.incomplete_frames
+    ))
+    .on_hover_text(on_hover);
+}
+
+fn build_payload_summary(ui: &mut egui::Ui, stream: &Stream) {
+    if let Some(stats) = &stream.mpeg_ts_stats {
+        build_mpeg_ts_summary(ui, stream, stats);
+        return;
+    }
+    let Some(opus) = &stream.opus_stats else {
+        ui.label("N/A");
+        return;
+    };
+
+    let ptime = match opus.get_mean_packetization_time() {
+        Some(ptime) => format!("{:.1} ms", ptime),
+        None => "N/A".to_string(),
+    };
+    let on_hover = format!(
+        "Opus packets: {}\nMean packetization time: {}\nDTX packets: {} ({:.2}%)\nPackets with FEC: {} ({:.2}%)",
+        opus.packets,
+        ptime,
+        opus.dtx_packets,
+        opus.get_dtx_ratio() * 100.0,
+        opus.fec_packets,
+        opus.get_fec_ratio() * 100.0
+    );
+
+    ui.label(format!(
+        "ptime {}\nDTX {:.1}%",
+        ptime,
+        opus.get_dtx_ratio() * 100.0
+    ))
+    .on_hover_text(on_hover);
+}
+
+fn build_mpeg_ts_summary(ui: &mut egui::Ui, stream: &Stream, stats: &MpegTsStats) {
+    let pcr_jitter = match stats.max_pcr_jitter {
+        Some(jitter) => format!("{:.3} ms", jitter * 1000.0),
+        None => "N/A".to_string(),
+    };
+    let mut on_hover = format!(
+        "Sync byte errors: {}\nContinuity counter errors: {}\nPAT errors: {}\nPMT errors: {}\nMax PCR jitter: {}\n",
+        stats.sync_byte_errors,
+        stats.continuity_errors,
+        stats.pat_errors,
+        stats.pmt_errors,
+        pcr_jitter
+    );
+    for (pid, pid_stats) in &stats.pids {
+        let stream_type = pid_stats
+            .stream_type
+            .map(stream_type_name)
+            .unwrap_or_else(|| "PSI/unknown".to_string());
+        on_hover.push_str(&format!(
+            "\nPID 0x{:04x} ({}): {} packets, {:.2} kbps",
+            pid,
+            stream_type,
+            pid_stats.packets,
+            stream.get_pid_bitrate(pid_stats) / 1000.0
+        ));
+    }
+
+    let errors =
+        stats.sync_byte_errors + stats.continuity_errors + stats.pat_errors + stats.pmt_errors;
+    ui.label(format!(
+        "{} PIDs, {} errors\nPCR jitter {}",
+        stats.pids.len(),
+        errors,
+        pcr_jitter
+    ))
+    .on_hover_text(on_hover);
+}
+
+fn build_speech_activity_label(ui: &mut egui::Ui, stream: &Stream) {
+    let Some(stats) = &stream.talkspurt_stats else {
+        ui.label("N/A");
+        return;
+    };
+
+    let activity = stream.get_speech_activity(stats) * 100.0;
+    let on_hover = format!(
+        "Talkspurts: {}\nSpeech time: {:.2} s\nSpeech activity: {:.2}%",
+        stats.talkspurts,
+        stats.speech_time.as_secs_f64(),
+        activity
+    );
+
+    ui.label(format!("{:.1}%\n{} talkspurts", activity, stats.talkspurts))
+        .on_hover_text(on_hover);
+}
+
+fn build_quality_label(ui: &mut egui::Ui, stream: &Stream) {
+    let Some(score) = stream.get_quality() else {
+        ui.label("N/A");
+        return;
+    };
+
+    let rtt = match score.rtt {
+        Some(rtt) => format!("{:.1} ms", rtt * 1000.0),
+        None => "N/A".to_string(),
+    };
+    let mut on_hover = format!(
+        "R-factor: {:.1}\nLoss: {:.2}%\nBurst ratio: {:.2}\nJitter: {:.3} ms\nRTT: {}",
+        score.r_factor,
+        score.loss * 100.0,
+        score.burst_ratio,
+        score.jitter * 1000.0,
+        rtt
+    );
+    let history = stream.get_quality_history();
+    if let Some(worst) = history
+        .iter()
+        .min_by(|a, b| a.score.mos.total_cmp(&b.score.mos))
+    {
+        on_hover.push_str(&format!(
+            "\n\nWorst window: {:.0}-{:.0} s, MOS {:.2}",
+            worst.start,
+            worst.start + QUALITY_WINDOW,
+            worst.score.mos
+        ));
+    }
+
+    ui.label(format!("{:.2}\nR {:.0}", score.mos, score.r_factor))
+        .on_hover_text(on_hover);
+}
+
+fn build_recovery_label(ui: &mut egui::Ui, recovery_stats: &Option<RecoveryStats>) {
+    let Some(stats) = recovery_stats
+        .as_ref()
+        .filter(|stats| stats.has_redundancy())
+    else {
+        ui.label("N/A");
+        return;
+    };
+
+    let on_hover = format!(
+        "Lost packets: {}\nRecoverable with FEC: {}\nRecoverable with RED: {}\n\
+         FEC packets: {}\nRED packets: {}",
+        stats.lost,
+        stats.recoverable_by_fec,
+        stats.recoverable_by_red,
+        stats.fec_packets,
+        stats.red_packets
+    );
+
+    ui.label(format!("{} of {} lost", stats.recoverable, stats.lost))
+        .on_hover_text(on_hover);
+}
+
+fn build_dtmf_label(ui: &mut egui::Ui, stream: &Stream) {
+    if stream.dtmf_events.is_empty() {
+        ui.label("N/A");
+        return;
+    }
+
+    let digits = stream
+        .dtmf_events
+        .iter()
+        .map(|event| event_name(event.event))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let on_hover = stream
+        .dtmf_events
+        .iter()
+        .map(|event| {
+            let time = event
+                .time
+                .saturating_sub(stream.get_first_time())
+                .as_secs_f64();
+            let duration = match event.get_duration() {
+                Some(duration) => format!("{:.0} ms", duration.as_secs_f64() * 1000.0),
+                None => format!("{} RTP units", event.duration),
+            };
+            format!(
+                "{} at {:.3} s, duration {}, volume -{} dBm0{}",
+                event_name(event.event),
+                time,
+                duration,
+                event.volume,
+                if event.ended { "" } else { " (no end packet)" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ui.label(digits).on_hover_text(on_hover);
+}
+
+fn build_rtt_plot(ui: &mut egui::Ui, stream: &Stream) {
+    if stream.rtt_samples.is_empty() {
+        return;
+    }
+
+    ui.vertical_centered_justified(|ui| {
+        let first_time = stream.rtt_samples.first().unwrap().time;
+        let get_points = |get_rtt: fn(&RttSample) -> Option<f64>| -> PlotPoints {
+            stream
+                .rtt_samples
+                .iter()
+                .filter_map(|sample| {
+                    let time = sample.time.saturating_sub(first_time).as_secs_f64();
+                    get_rtt(sample).map(|rtt| [time, rtt * 1000.0])
+                })
+                .collect()
+        };
+        let sender_line = Line::new(get_points(|sample| sample.sender_rtt)).name("sender");
+        let capture_line = Line::new(get_points(|sample| sample.capture_rtt)).name("capture point");
+
+        let key = format!(
+            "rtt{}{}{}{}",
+            stream.ssrc, stream.source_addr, stream.destination_addr, stream.protocol
+        );
+        Plot::new(key)
+            .show_background(false)
+            .show_axes([true, true])
+            .label_formatter(|name, value| {
+                format!("{}\ntime: {:.2} s\nRTT = {:.1} ms", name, value.x, value.y)
+            })
+            .set_margin_fraction(Vec2::new(0.1, 0.1))
+            .allow_scroll(false)
+            .show(ui, |plot_ui| {
+                plot_ui.line(sender_line);
+                plot_ui.line(capture_line);
+            });
+        ui.add_space(7.0);
+    });
+}
+
 fn build_jitter_plot(ui: &mut egui::Ui, stream: &Stream) {
     ui.vertical_centered_justified(|ui| {
         let points: PlotPoints = stream
@@ -6,7 +6,11 @@ pub enum Tab {
     RtpPackets,
     RtcpPackets,
     Streams,
+    Frames,
     Plot,
+    LipSync,
+    Twcc,
+    Events,
 }
 
 impl Tab {
@@ -16,7 +20,11 @@ impl Tab {
             Self::RtpPackets,
             Self::RtcpPackets,
             Self::Streams,
+            Self::Frames,
             Self::Plot,
+            Self::LipSync,
+            Self::Twcc,
+            Self::Events,
         ]
     }
 
@@ -34,7 +42,11 @@ impl fmt::Display for Tab {
             Self::RtpPackets => "🔈RTP Packets",
             Self::RtcpPackets => "📃 RTCP Packets",
             Self::Streams => "🔴 Streams",
+            Self::Frames => "🎞 Frames",
             Self::Plot => "📈 Plot",
+            Self::LipSync => "👄 Lip sync",
+            Self::Twcc => "🚦 TWCC",
+            Self::Events => "🔔 Events",
         };
 
         write!(f, "{}", ret)
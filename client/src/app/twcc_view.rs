@@ -0,0 +1,253 @@
+use crate::streams::twcc::{TwccPacket, TwccStats};
+use crate::streams::RefStreams;
+use eframe::epaint::Color32;
+use egui::plot::{HLine, Line, LineStyle, Plot, PlotPoints};
+use egui::{ComboBox, RichText};
+use egui_extras::{Column, TableBody, TableBuilder};
+use rtpeeker_common::packet::TransportProtocol;
+use std::net::SocketAddr;
+
+type Transport = (SocketAddr, SocketAddr, TransportProtocol);
+
+pub struct TwccView {
+    streams: RefStreams,
+    selected_transport: Option<Transport>,
+}
+
+impl TwccView {
+    pub fn new(streams: RefStreams) -> Self {
+        Self {
+            streams,
+            selected_transport: None,
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("twcc_transport_picker").show(ctx, |ui| {
+            self.build_transport_picker(ui);
+        });
+
+        let streams = self.streams.borrow();
+        let stats = self
+            .selected_transport
+            .and_then(|transport| streams.get_twcc_stats(transport));
+        let Some(stats) = stats else {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.label("No packets with transport-wide sequence numbers on the transport");
+            });
+            return;
+        };
+
+        egui::TopBottomPanel::top("twcc_summary").show(ctx, |ui| {
+            build_summary(ui, &stats);
+        });
+        egui::TopBottomPanel::bottom("twcc_packets")
+            .resizable(true)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                build_table(ui, &stats);
+            });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            build_plots(ui, &stats);
+        });
+    }
+
+    fn build_transport_picker(&mut self, ui: &mut egui::Ui) {
+        let transports = self.streams.borrow().get_twcc_transports().to_vec();
+
+        let selected = transports
+            .iter()
+            .find(|transport| Some(**transport) == self.selected_transport)
+            .or(transports.first());
+        self.selected_transport = selected.copied();
+        let selected_text = selected.map_or(
+            "No transports with header extensions".to_string(),
+            format_transport,
+        );
+
+        ui.horizontal(|ui| {
+            ui.label("Transport: ");
+            ComboBox::from_id_source("twcc_transport_picker")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for transport in transports {
+                        let label = format_transport(&transport);
+                        ui.selectable_value(&mut self.selected_transport, Some(transport), label);
+                    }
+                });
+        });
+    }
+}
+
+fn build_summary(ui: &mut egui::Ui, stats: &TwccStats) {
+    egui::Grid::new("twcc_summary_grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label(RichText::new("Extension id:").strong());
+            let resp = ui.label(stats.extension_id.to_string());
+            if stats.is_extension_id_inferred {
+                resp.on_hover_text("Not present in SDP, inferred from consecutive values");
+            }
+            ui.end_row();
+
+            ui.label(RichText::new("SSRCs:").strong());
+            let ssrcs = stats
+                .ssrcs
+                .iter()
+                .map(|ssrc| format!("{:x}", ssrc))
+                .collect::<Vec<_>>()
+                .join(", ");
+            ui.label(ssrcs);
+            ui.end_row();
+
+            ui.label(RichText::new("Packets:").strong());
+            ui.label(format!(
+                "{} sent, {} reported in {} feedback packets",
+                stats.packets.len(),
+                stats.get_reported_count(),
+                stats.feedbacks
+            ));
+            ui.end_row();
+
+            ui.label(RichText::new("Reported loss:").strong());
+            let loss_label = match stats.get_reported_loss() {
+                Some(loss) => format!("{} ({:.2}%)", stats.get_reported_lost_count(), loss * 100.0),
+                None => "N/A".to_string(),
+            };
+            ui.label(loss_label);
+            ui.end_row();
+
+            ui.label(RichText::new("Max delay gradient:").strong());
+            let gradient_label = match stats.get_max_delay_gradient() {
+                Some(gradient) => format!("{:+.1} ms", gradient),
+                None => "N/A".to_string(),
+            };
+            ui.label(gradient_label);
+            ui.end_row();
+        });
+}
+
+fn build_plots(ui: &mut egui::Ui, stats: &TwccStats) {
+    let height = ui.available_height() / 2.0 - ui.spacing().item_spacing.y;
+
+    Plot::new("twcc-delay-gradient-plot")
+        .height(height)
+        .include_x(0.0)
+        .label_formatter(|_, value| {
+            format!("time: {:.2} s\ngradient = {:+.2} ms", value.x, value.y)
+        })
+        .show(ui, |plot_ui| {
+            plot_ui.hline(
+                HLine::new(0.0)
+                    .color(Color32::GRAY)
+                    .style(LineStyle::dashed_loose()),
+            );
+            let points: PlotPoints = stats
+                .delay_gradients
+                .iter()
+                .map(|delay_gradient| [delay_gradient.time, delay_gradient.gradient])
+                .collect();
+            plot_ui.line(Line::new(points).name("Delay gradient"));
+        });
+
+    Plot::new("twcc-bitrate-plot")
+        .height(height)
+        .include_x(0.0)
+        .include_y(0.0)
+        .label_formatter(|_, value| {
+            format!("time: {:.2} s\nbitrate = {:.0} kbps", value.x, value.y)
+        })
+        .show(ui, |plot_ui| {
+            let points: PlotPoints = stats
+                .bitrates
+                .iter()
+                .map(|(time, bitrate)| [*time, *bitrate])
+                .collect();
+            plot_ui.line(Line::new(points).name("Sending bitrate"));
+        });
+}
+
+fn build_table(ui: &mut egui::Ui, stats: &TwccStats) {
+    let header_labels = [
+        ("Seq. no.", "Extended transport-wide sequence number"),
+        ("SSRC", "Stream the packet belongs to"),
+        (
+            "Time",
+            "Capture time relative to the first packet on the transport",
+        ),
+        ("Size", "Length of the packet"),
+        (
+            "Send delta",
+            "Capture time difference to the previous packet in sequence number order",
+        ),
+        (
+            "Receive delta",
+            "Reported receive time difference to the previous received packet",
+        ),
+        ("Status", "Status of the packet reported in the feedback"),
+    ];
+    TableBuilder::new(ui)
+        .striped(true)
+        .resizable(true)
+        .columns(Column::remainder().at_least(80.0), 7)
+        .header(30.0, |mut header| {
+            header_labels.iter().for_each(|(label, desc)| {
+                header.col(|ui| {
+                    ui.heading(label.to_string())
+                        .on_hover_text(desc.to_string());
+                });
+            });
+        })
+        .body(|body| {
+            build_table_body(body, stats);
+        });
+}
+
+fn build_table_body(body: TableBody, stats: &TwccStats) {
+    body.rows(25.0, stats.packets.len(), |ix, mut row| {
+        let packet = &stats.packets[ix];
+
+        row.col(|ui| {
+            ui.label(packet.sequence_number.to_string());
+        });
+        row.col(|ui| {
+            ui.label(format!("{:x}", packet.ssrc));
+        });
+        row.col(|ui| {
+            let time = packet.send_time.saturating_sub(stats.first_time);
+            ui.label(format!("{:.4}", time.as_secs_f64()));
+        });
+        row.col(|ui| {
+            ui.label(format!("{} B", packet.bytes));
+        });
+        row.col(|ui| {
+            ui.label(format_delta(packet.send_delta));
+        });
+        row.col(|ui| {
+            ui.label(format_delta(packet.receive_delta));
+        });
+        row.col(|ui| {
+            ui.label(get_status(packet));
+        });
+    });
+}
+
+fn format_delta(delta: Option<f64>) -> String {
+    match delta {
+        Some(delta) => format!("{:+.3} ms", delta),
+        None => "N/A".to_string(),
+    }
+}
+
+fn get_status(packet: &TwccPacket) -> RichText {
+    match (packet.is_reported, packet.receive_time) {
+        (true, Some(_)) => RichText::new("Received"),
+        (true, None) => RichText::new("Lost").color(Color32::RED),
+        (false, _) => RichText::new("Not reported").weak(),
+    }
+}
+
+fn format_transport(transport: &Transport) -> String {
+    let (source_addr, destination_addr, protocol) = transport;
+    format!("{} → {} ({})", source_addr, destination_addr, protocol)
+}
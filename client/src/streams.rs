@@ -1,60 +1,519 @@
+use events::QualityEvent;
+use lip_sync::LipSyncStats;
 use packets::Packets;
+use recovery::{RecoveryStats, RetransmissionStats};
 use rtpeeker_common::packet::SessionPacket;
+use rtpeeker_common::rtcp::TransportFeedback;
+use rtpeeker_common::rtp::fec::{FecPayload, FecScheme};
+use rtpeeker_common::rtp::payload::PayloadInfo;
+use rtpeeker_common::rtp::payload_type::MediaType;
+use rtpeeker_common::rtp::stream_id::{parse_sdes_value, MID_URI};
+use rtpeeker_common::rtp::RtpPacket;
 use rtpeeker_common::StreamKey;
-use rtpeeker_common::{packet::TransportProtocol, Packet, RtcpPacket};
-use std::cell::RefCell;
+use rtpeeker_common::{
+    packet::TransportProtocol, DecodeRule, Packet, QualityRule, RtcpPacket, Sdp,
+};
+use std::cell::{OnceCell, RefCell};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::rc::Rc;
 use stream::Stream;
+use twcc::TwccStats;
 
+pub mod events;
+pub mod frames;
+pub mod lip_sync;
+pub mod loss;
 mod packets;
+pub mod quality;
+pub mod recovery;
 pub mod stream;
+#[cfg(test)]
+mod test_utils;
+pub mod twcc;
 
 pub type RefStreams = Rc<RefCell<Streams>>;
+type Transport = (SocketAddr, SocketAddr, TransportProtocol);
+
+// media source with its simulcast layers and repair streams
+#[derive(Debug, Clone)]
+pub struct StreamGroup {
+    pub name: String,
+    pub transport: (SocketAddr, SocketAddr, TransportProtocol),
+    pub keys: Vec<StreamKey>,
+    pub mid: Option<String>,
+}
+
+// statistics spanning all of the streams are too expensive to build on every frame,
+// so they're built on the first use and dropped whenever the streams change
+#[derive(Debug, Default)]
+struct StreamsCache {
+    groups: OnceCell<Vec<StreamGroup>>,
+    recovery_stats: OnceCell<HashMap<StreamKey, RecoveryStats>>,
+    // RTX stream key to its primary stream key
+    rtx_primary_keys: OnceCell<HashMap<StreamKey, StreamKey>>,
+    // by the primary stream key
+    retransmission_stats: OnceCell<HashMap<StreamKey, RetransmissionStats>>,
+    twcc_transports: OnceCell<Vec<Transport>>,
+    // built only for the transports that were asked for
+    twcc_stats: RefCell<HashMap<Transport, Option<Rc<TwccStats>>>>,
+}
 
 #[derive(Debug, Default)]
 pub struct Streams {
     pub packets: Packets,
     pub streams: HashMap<StreamKey, Stream>,
+    sdps: HashMap<StreamKey, Sdp>,
+    cache: StreamsCache,
+    pub decode_rules: Vec<DecodeRule>,
+    quality_rules: Vec<QualityRule>,
+    is_outdated: bool,
 }
 
 impl Streams {
     pub fn clear(&mut self) {
         self.packets.clear();
         self.streams.clear();
+        self.invalidate_cache();
+        // the server sends SDPs and decode rules again on refetch
+        self.sdps.clear();
+        self.decode_rules.clear();
+    }
+
+    pub fn has_sdp(&self, stream_key: &StreamKey) -> bool {
+        self.sdps.contains_key(stream_key)
+    }
+
+    pub fn add_sdp(&mut self, stream_key: StreamKey, sdp: Sdp) {
+        // SDP is applied when the stream is created, and changes how the payload
+        // is interpreted, so all of the packets need to be parsed again
+        self.sdps.insert(stream_key, sdp);
+        self.recalculate();
+    }
+
+    pub fn remove_sdp(&mut self, stream_key: &StreamKey) {
+        if self.sdps.remove(stream_key).is_some() {
+            self.recalculate();
+        }
+    }
+
+    // has to be called after any change of the streams
+    pub fn invalidate_cache(&mut self) {
+        self.cache = StreamsCache::default();
+    }
+
+    pub fn get_quality_rules(&self) -> &[QualityRule] {
+        &self.quality_rules
+    }
+
+    pub fn set_quality_rules(&mut self, rules: Vec<QualityRule>) {
+        // rules are sent again on refetch, usually without changes
+        if self.quality_rules == rules {
+            return;
+        }
+        self.quality_rules = rules;
+        self.recalculate();
+    }
+
+    // events of all of the streams, in the order of occurrence
+    pub fn get_events(&self) -> Vec<(&Stream, &QualityEvent)> {
+        let mut events: Vec<_> = self
+            .streams
+            .values()
+            .flat_map(|stream| stream.events.iter().map(move |event| (stream, event)))
+            .collect();
+        events.sort_by_key(|(_, event)| (event.time, event.packet_id));
+        events
+    }
+
+    pub fn get_recovery_stats(&self, stream_key: &StreamKey) -> Option<&RecoveryStats> {
+        self.cache
+            .recovery_stats
+            .get_or_init(|| self.build_recovery_stats())
+            .get(stream_key)
+    }
+
+    fn build_recovery_stats(&self) -> HashMap<StreamKey, RecoveryStats> {
+        // ULPFEC protects packets of its own stream,
+        // FlexFEC is sent in a separate stream with the protected SSRC in its header (draft 03),
+        // in the CSRC list, or declared with `a=ssrc-group:FEC-FR` (RFC 8627)
+        let mut ulpfec_payloads: HashMap<&StreamKey, Vec<(usize, &FecPayload)>> = HashMap::new();
+        let mut flexfec_payloads: HashMap<u32, Vec<(usize, &FecPayload)>> = HashMap::new();
+        for (key, fec_stream) in &self.streams {
+            for rtp_info in &fec_stream.rtp_packets {
+                let Some(PayloadInfo::Fec(fec)) = rtp_info.get_primary_payload_info() else {
+                    continue;
+                };
+                let protected_ssrc = match (fec.scheme, fec.protected_ssrc) {
+                    (_, Some(ssrc)) => Some(ssrc),
+                    (FecScheme::Ulpfec, None) => None,
+                    (FecScheme::Flexfec, None) => {
+                        rtp_info.packet.csrc.first().copied().or_else(|| {
+                            fec_stream
+                                .get_sdp()?
+                                .get_fec_fr_protected_ssrc(fec_stream.ssrc)
+                        })
+                    }
+                };
+                match (fec.scheme, protected_ssrc) {
+                    (FecScheme::Ulpfec, _) => ulpfec_payloads
+                        .entry(key)
+                        .or_default()
+                        .push((rtp_info.id, fec)),
+                    (FecScheme::Flexfec, Some(ssrc)) => flexfec_payloads
+                        .entry(ssrc)
+                        .or_default()
+                        .push((rtp_info.id, fec)),
+                    (FecScheme::Flexfec, None) => {}
+                }
+            }
+        }
+
+        self.streams
+            .iter()
+            .map(|(key, stream)| {
+                let fec_payloads = ulpfec_payloads
+                    .get(key)
+                    .into_iter()
+                    .chain(flexfec_payloads.get(&stream.ssrc))
+                    .flatten()
+                    .copied();
+                (*key, RecoveryStats::build(stream, fec_payloads))
+            })
+            .collect()
+    }
+
+    pub fn get_rtx_primary_key(&self, rtx_key: &StreamKey) -> Option<StreamKey> {
+        self.cache
+            .rtx_primary_keys
+            .get_or_init(|| {
+                self.streams
+                    .keys()
+                    .filter_map(|key| Some((*key, self.find_rtx_primary_key(key)?)))
+                    .collect()
+            })
+            .get(rtx_key)
+            .copied()
+    }
+
+    // RTX stream is associated with its primary stream either by `a=ssrc-group:FID`
+    // or by the `apt` parameter, the latter only if a single stream matches it
+    fn find_rtx_primary_key(&self, rtx_key: &StreamKey) -> Option<StreamKey> {
+        let rtx_stream = self.streams.get(rtx_key).filter(|stream| stream.is_rtx)?;
+        // payload type could not be resolved as RTX without SDP
+        let sdp = rtx_stream.get_sdp()?;
+
+        let (source_addr, destination_addr, protocol, _) = *rtx_key;
+        let candidates = self.streams.iter().filter(|(key, stream)| {
+            key.0 == source_addr && key.1 == destination_addr && key.2 == protocol && !stream.is_rtx
+        });
+
+        if let Some(primary_ssrc) = sdp.get_fid_primary_ssrc(rtx_stream.ssrc) {
+            return candidates
+                .map(|(key, _)| *key)
+                .find(|key| key.3 == primary_ssrc);
+        }
+
+        // simulcast repair streams carry the RID of the repaired stream
+        if let Some(repaired_rid) = &rtx_stream.repaired_rid {
+            return candidates
+                .filter(|(_, stream)| stream.mid == rtx_stream.mid)
+                .find(|(_, stream)| stream.rid.as_ref() == Some(repaired_rid))
+                .map(|(key, _)| *key);
+        }
+
+        let apt = rtx_stream
+            .payload_types
+            .iter()
+            .find_map(|pt| sdp.rtx_payload_types.get(&pt.id))?;
+        let mut matching = candidates
+            .filter(|(_, stream)| stream.payload_types.iter().any(|pt| pt.id == *apt))
+            .map(|(key, _)| *key);
+
+        match (matching.next(), matching.next()) {
+            (Some(key), None) => Some(key),
+            _ => None,
+        }
+    }
+
+    pub fn get_retransmission_stats(&self, stream_key: &StreamKey) -> Option<&RetransmissionStats> {
+        self.cache
+            .retransmission_stats
+            .get_or_init(|| {
+                let mut stats = HashMap::new();
+                for (rtx_key, rtx_stream) in &self.streams {
+                    let Some(primary_key) = self.get_rtx_primary_key(rtx_key) else {
+                        continue;
+                    };
+                    let stream = self.streams.get(&primary_key).unwrap();
+                    stats
+                        .entry(primary_key)
+                        .or_insert_with(|| RetransmissionStats::build(stream, rtx_stream));
+                }
+                stats
+            })
+            .get(stream_key)
+    }
+
+    // streams of a single media source (same transport and MID, or a RTX stream
+    // and its primary stream) are grouped, sorted by aliases
+    pub fn get_groups(&self) -> &[StreamGroup] {
+        self.cache.groups.get_or_init(|| self.build_groups())
+    }
+
+    fn build_groups(&self) -> Vec<StreamGroup> {
+        let mut keys: Vec<_> = self.streams.keys().copied().collect();
+        keys.sort_by_key(|key| {
+            let alias = &self.streams.get(key).unwrap().alias;
+            (alias.len(), alias.clone())
+        });
+
+        let mut groups: Vec<StreamGroup> = Vec::new();
+        for key in keys {
+            let stream = self.streams.get(&key).unwrap();
+            let primary = self
+                .get_rtx_primary_key(&key)
+                .and_then(|primary_key| self.streams.get(&primary_key))
+                .unwrap_or(stream);
+            let mid = stream.mid.clone().or_else(|| primary.mid.clone());
+            let name = match &mid {
+                Some(mid) => format!("MID {}", mid),
+                None => primary.alias.clone(),
+            };
+            let transport = (key.0, key.1, key.2);
+
+            let group = groups
+                .iter_mut()
+                .find(|group| group.transport == transport && group.name == name);
+            match group {
+                Some(group) => group.keys.push(key),
+                None => groups.push(StreamGroup {
+                    name,
+                    transport,
+                    keys: vec![key],
+                    mid,
+                }),
+            }
+        }
+
+        // media streams in the simulcast order first, then the repair streams
+        for group in groups.iter_mut() {
+            group.keys.sort_by_cached_key(|key| {
+                let stream = self.streams.get(key).unwrap();
+                let is_repair = stream.is_rtx || stream.repaired_rid.is_some();
+                let rid_index = stream
+                    .rid
+                    .as_ref()
+                    .zip(stream.get_sdp())
+                    .and_then(|(rid, sdp)| sdp.get_rid_index(rid))
+                    .unwrap_or(usize::MAX);
+                (
+                    is_repair,
+                    rid_index,
+                    stream.alias.len(),
+                    stream.alias.clone(),
+                )
+            });
+        }
+
+        groups
+    }
+
+    // audio and video streams sharing the RTCP CNAME are paired, repair streams are skipped
+    pub fn get_lip_sync_stats(&self) -> Vec<LipSyncStats> {
+        let mut keys: Vec<_> = self
+            .streams
+            .iter()
+            .filter(|(_, stream)| !stream.is_rtx && stream.repaired_rid.is_none())
+            .map(|(key, _)| *key)
+            .collect();
+        keys.sort_by_key(|key| {
+            let alias = &self.streams.get(key).unwrap().alias;
+            (alias.len(), alias.clone())
+        });
+
+        let mut stats = Vec::new();
+        for audio_key in &keys {
+            let audio = self.streams.get(audio_key).unwrap();
+            let (Some(cname), Some(MediaType::Audio)) = (&audio.cname, audio.get_media_type())
+            else {
+                continue;
+            };
+
+            for video_key in &keys {
+                let video = self.streams.get(video_key).unwrap();
+                if video.cname.as_ref() != Some(cname)
+                    || !matches!(video.get_media_type(), Some(MediaType::Video))
+                {
+                    continue;
+                }
+                stats.extend(LipSyncStats::build(
+                    cname.clone(),
+                    (*audio_key, audio),
+                    (*video_key, video),
+                ));
+            }
+        }
+
+        stats
+    }
+
+    // transports carrying RTP packets with header extensions, in the order of stream aliases
+    pub fn get_twcc_transports(&self) -> &[(SocketAddr, SocketAddr, TransportProtocol)] {
+        self.cache
+            .twcc_transports
+            .get_or_init(|| self.build_twcc_transports())
+    }
+
+    fn build_twcc_transports(&self) -> Vec<(SocketAddr, SocketAddr, TransportProtocol)> {
+        let mut streams: Vec<_> = self
+            .streams
+            .iter()
+            .filter(|(_, stream)| {
+                stream
+                    .rtp_packets
+                    .iter()
+                    .any(|rtp_info| !rtp_info.packet.extensions.is_empty())
+            })
+            .collect();
+        streams.sort_by_key(|(_, stream)| (stream.alias.len(), stream.alias.clone()));
+
+        let mut transports = Vec::new();
+        for (key, _) in streams {
+            let transport = (key.0, key.1, key.2);
+            if !transports.contains(&transport) {
+                transports.push(transport);
+            }
+        }
+
+        transports
+    }
+
+    // TWCC feedback is sent by the receiver in the opposite direction, on the same ports
+    // with rtcp-mux, otherwise it's matched by the media SSRC it refers to
+    pub fn get_twcc_stats(
+        &self,
+        transport: (SocketAddr, SocketAddr, TransportProtocol),
+    ) -> Option<Rc<TwccStats>> {
+        self.cache
+            .twcc_stats
+            .borrow_mut()
+            .entry(transport)
+            .or_insert_with(|| self.build_twcc_stats(transport).map(Rc::new))
+            .clone()
+    }
+
+    fn build_twcc_stats(
+        &self,
+        transport: (SocketAddr, SocketAddr, TransportProtocol),
+    ) -> Option<TwccStats> {
+        let (source_addr, destination_addr, protocol) = transport;
+        let streams: Vec<_> = self
+            .streams
+            .iter()
+            .filter(|(key, _)| {
+                key.0 == source_addr && key.1 == destination_addr && key.2 == protocol
+            })
+            .map(|(_, stream)| stream)
+            .collect();
+
+        let ssrcs: Vec<_> = streams.iter().map(|stream| stream.ssrc).collect();
+        let is_feedback = |packet: &Packet, feedback: &TransportFeedback| {
+            (packet.source_addr == destination_addr && packet.destination_addr == source_addr)
+                || ssrcs.contains(&feedback.media_ssrc)
+        };
+
+        let feedbacks: Vec<_> = self
+            .packets
+            .values()
+            .filter(|packet| packet.transport_protocol == protocol)
+            .filter_map(|packet| match &packet.contents {
+                SessionPacket::Rtcp(packs) => Some((packet, packs)),
+                _ => None,
+            })
+            .flat_map(|(packet, packs)| {
+                packs.iter().filter_map(move |pack| match pack {
+                    RtcpPacket::TransportFeedback(feedback) if is_feedback(packet, feedback) => {
+                        Some((packet.timestamp, feedback))
+                    }
+                    _ => None,
+                })
+            })
+            .collect();
+
+        TwccStats::build(&streams, &feedbacks)
     }
 
     pub fn add_packet(&mut self, packet: Packet) {
         let is_new = self.packets.is_new(&packet);
 
         if is_new {
-            handle_packet(&mut self.streams, &packet);
+            handle_packet(
+                &mut self.streams,
+                &HashMap::new(),
+                &self.sdps,
+                &self.quality_rules,
+                &packet,
+            );
             self.packets.add_packet(packet);
+            self.invalidate_cache();
         } else {
             // if the packet is not new (its id is smaller that the last packet's id)
-            // that this must be result of `parse_as` request or refetch (tho packets should be
-            // pruned before refetch) in that case, recalculate everything,
-            // this can be optimised if it proves to be to slow
+            // that this must be result of `parse_as` request, refetch (tho packets should be
+            // pruned before refetch) or confirmation of a RTP flow, in that case, recalculate
+            // everything, but only once for all of the received packets
             self.packets.add_packet(packet);
+            self.is_outdated = true;
+        }
+    }
+
+    pub fn recalculate_if_outdated(&mut self) {
+        if self.is_outdated {
             self.recalculate();
         }
     }
 
     fn recalculate(&mut self) {
+        // aliases might have been changed by the user, so they're carried over
+        let aliases: HashMap<_, _> = self
+            .streams
+            .drain()
+            .map(|(key, stream)| (key, stream.alias))
+            .collect();
         let mut new_streams = HashMap::new();
 
-        self.packets
-            .values()
-            .for_each(|packet| handle_packet(&mut new_streams, packet));
+        self.packets.values().for_each(|packet| {
+            handle_packet(
+                &mut new_streams,
+                &aliases,
+                &self.sdps,
+                &self.quality_rules,
+                packet,
+            )
+        });
 
         self.streams = new_streams;
+        self.invalidate_cache();
+        self.is_outdated = false;
     }
 }
 
 // this function need to take streams as an argument as opposed to methods on `Streams`
 // to make `Streams::recalculate` work, dunno if there's a better way
-fn handle_packet(streams: &mut HashMap<StreamKey, Stream>, packet: &Packet) {
+fn handle_packet(
+    streams: &mut HashMap<StreamKey, Stream>,
+    aliases: &HashMap<StreamKey, String>,
+    sdps: &HashMap<StreamKey, Sdp>,
+    rules: &[QualityRule],
+    packet: &Packet,
+) {
+    // streams that stopped won't get another packet to evaluate the gap on
+    if rules.iter().any(|rule| matches!(rule, QualityRule::Gap(_))) {
+        for stream in streams.values_mut() {
+            stream.check_idle_quality_rules(rules, packet.timestamp);
+        }
+    }
+
     match packet.contents {
         SessionPacket::Rtp(ref rtp) => {
             let stream_key = (
@@ -66,8 +525,20 @@ fn handle_packet(streams: &mut HashMap<StreamKey, Stream>, packet: &Packet) {
 
             if let Some(stream) = streams.get_mut(&stream_key) {
                 stream.add_rtp_packet(packet, rtp);
+                stream.check_quality_rules(rules);
             } else {
-                let new_stream = Stream::new(packet, rtp, int_to_letter(streams.len()));
+                let sdp = find_sdp(sdps, &stream_key, rtp).cloned();
+                let alias = aliases
+                    .get(&stream_key)
+                    .cloned()
+                    .unwrap_or_else(|| get_free_alias(streams, aliases));
+                let mut new_stream = Stream::new(packet, rtp, alias, sdp);
+                if rules.contains(&QualityRule::SsrcChange) {
+                    new_stream
+                        .events
+                        .extend(events::get_ssrc_change(streams, &new_stream));
+                }
+                new_stream.check_quality_rules(rules);
                 streams.insert(stream_key, new_stream);
             }
         }
@@ -94,6 +565,17 @@ fn handle_packet(streams: &mut HashMap<StreamKey, Stream>, packet: &Packet) {
                         stream.add_rtcp_packet(packet.id, packet.timestamp, pack);
                     }
                 }
+
+                let (reporter_ssrc, reports) = match pack {
+                    RtcpPacket::SenderReport(sr) => (sr.ssrc, &sr.reports),
+                    RtcpPacket::ReceiverReport(rr) => (rr.ssrc, &rr.reports),
+                    _ => continue,
+                };
+                for report in reports {
+                    if let Some(stream) = get_reported_stream(streams, packet, report.ssrc) {
+                        stream.add_reception_report(packet.timestamp, reporter_ssrc, report);
+                    }
+                }
             }
         }
         // Ignoring other types of RTCP packet for now
@@ -101,6 +583,40 @@ fn handle_packet(streams: &mut HashMap<StreamKey, Stream>, packet: &Packet) {
     };
 }
 
+// SDP set for other stream of the same media section applies as well,
+// if it declares the SSRC (e.g. RTX SSRC in `a=ssrc-group:FID`),
+// or if the packet carries its MID in the header extension it declares (BUNDLE, see RFC 8843)
+fn find_sdp<'a>(
+    sdps: &'a HashMap<StreamKey, Sdp>,
+    stream_key: &StreamKey,
+    rtp: &RtpPacket,
+) -> Option<&'a Sdp> {
+    if let Some(sdp) = sdps.get(stream_key) {
+        return Some(sdp);
+    }
+
+    let (source_addr, destination_addr, protocol, ssrc) = *stream_key;
+    let same_transport = sdps
+        .iter()
+        .filter(|(key, _)| key.0 == source_addr && key.1 == destination_addr && key.2 == protocol)
+        .map(|(_, sdp)| sdp);
+
+    let is_mid_matching = |sdp: &Sdp| {
+        let Some(mid) = &sdp.mid else {
+            return false;
+        };
+        sdp.get_extension_id(MID_URI)
+            .and_then(|id| rtp.get_extension(id))
+            .and_then(parse_sdes_value)
+            .is_some_and(|value| value == *mid)
+    };
+
+    same_transport
+        .clone()
+        .find(|sdp| sdp.declares_ssrc(ssrc))
+        .or_else(|| same_transport.clone().find(|sdp| is_mid_matching(sdp)))
+}
+
 fn get_rtcp_stream(
     streams: &mut HashMap<StreamKey, Stream>,
     mut source_addr: SocketAddr,
@@ -119,6 +635,39 @@ fn get_rtcp_stream(
     }
 }
 
+// aliases carried over by `Streams::recalculate` might not follow the order of the streams,
+// so the first letter not taken by any of the streams is used
+fn get_free_alias(
+    streams: &HashMap<StreamKey, Stream>,
+    aliases: &HashMap<StreamKey, String>,
+) -> String {
+    (0..)
+        .map(int_to_letter)
+        .find(|alias| {
+            !streams.values().any(|stream| stream.alias == *alias)
+                && !aliases.values().any(|other| other == alias)
+        })
+        .unwrap()
+}
+
+// reports are sent by the receiver in the opposite direction,
+// not necessarily from the stream's ports
+fn get_reported_stream<'a>(
+    streams: &'a mut HashMap<StreamKey, Stream>,
+    packet: &Packet,
+    ssrc: u32,
+) -> Option<&'a mut Stream> {
+    streams
+        .iter_mut()
+        .find(|(key, _)| {
+            key.0.ip() == packet.destination_addr.ip()
+                && key.1.ip() == packet.source_addr.ip()
+                && key.2 == packet.transport_protocol
+                && key.3 == ssrc
+        })
+        .map(|(_, stream)| stream)
+}
+
 fn int_to_letter(unique_id: usize) -> String {
     if unique_id == 0 {
         return String::from("A");
@@ -134,3 +683,68 @@ fn int_to_letter(unique_id: usize) -> String {
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rtpeeker_common::packet::SessionProtocol;
+    use test_utils::{build_packet, build_twcc_rtp, capture_time};
+
+    fn build_feedback_packet(
+        id: usize,
+        source: &str,
+        destination: &str,
+        media_ssrc: u32,
+    ) -> Packet {
+        let feedback = TransportFeedback {
+            sender_ssrc: 1,
+            media_ssrc,
+            base_sequence_number: 100,
+            feedback_count: id as u8,
+            receive_times: vec![Some(0), None],
+        };
+        let mut packet = build_packet(id, capture_time(500), &build_twcc_rtp(0));
+        packet.source_addr = source.parse().unwrap();
+        packet.destination_addr = destination.parse().unwrap();
+        packet.session_protocol = SessionProtocol::Rtcp;
+        packet.contents = SessionPacket::Rtcp(vec![RtcpPacket::TransportFeedback(feedback)]);
+        packet
+    }
+
+    #[test]
+    fn matches_twcc_feedback_by_transport_or_media_ssrc() {
+        let mut streams = Streams::default();
+        for sn in 0..10 {
+            let rtp = build_twcc_rtp(sn);
+            let packet = build_packet(sn as usize, capture_time(sn as u64 * 20), &rtp);
+            streams.add_packet(packet);
+        }
+        // sent back on the same ports, with a separate RTCP port and unrelated
+        streams.add_packet(build_feedback_packet(
+            10,
+            "10.0.0.2:5000",
+            "10.0.0.1:5000",
+            0,
+        ));
+        streams.add_packet(build_feedback_packet(
+            11,
+            "10.0.0.2:5001",
+            "10.0.0.1:5001",
+            0x1234,
+        ));
+        streams.add_packet(build_feedback_packet(
+            12,
+            "10.0.0.2:5001",
+            "10.0.0.1:5001",
+            0x5678,
+        ));
+
+        let transports = streams.get_twcc_transports().to_vec();
+        assert_eq!(transports.len(), 1);
+        let stats = streams.get_twcc_stats(transports[0]).unwrap();
+
+        assert_eq!(stats.feedbacks, 2);
+        assert_eq!(stats.get_reported_count(), 2);
+        assert_eq!(stats.get_reported_lost_count(), 1);
+    }
+}
@@ -0,0 +1,243 @@
+use super::stream::Stream;
+use rtpeeker_common::{QualityRule, StreamKey};
+use std::collections::HashMap;
+use std::time::Duration;
+
+// quality rule starting to hold for a stream
+#[derive(Debug, Clone)]
+pub struct QualityEvent {
+    pub time: Duration,
+    // packet that triggered the event
+    pub packet_id: usize,
+    pub rule: QualityRule,
+    pub description: String,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct RuleState {
+    // the condition held after the previous packet
+    is_active: bool,
+    // index of the first packet in the loss window
+    window_start: usize,
+    // the gap after the latest packet was already reported, see `evaluate_idle`
+    is_gap_reported: bool,
+}
+
+// evaluates the rule after the stream's latest packet,
+// an event fires when the condition starts to hold
+pub fn evaluate(
+    stream: &Stream,
+    rule: &QualityRule,
+    state: &mut RuleState,
+) -> Option<QualityEvent> {
+    let rtp_info = stream.rtp_packets.last()?;
+
+    let (is_active, description) = match *rule {
+        QualityRule::Jitter(threshold) => {
+            let jitter = rtp_info.jitter? * 1000.0;
+            (jitter > threshold, format!("Jitter of {:.1} ms", jitter))
+        }
+        QualityRule::Loss(threshold, window) => {
+            let loss = get_window_loss(stream, window, &mut state.window_start)? * 100.0;
+            let description = format!("{:.1}% of packets lost over {} s", loss, window);
+            (loss > threshold, description)
+        }
+        QualityRule::Gap(threshold) => {
+            let gap = rtp_info.time_delta.as_secs_f64() * 1000.0;
+            // silence suppression is not an issue
+            let is_gap = gap > threshold && !rtp_info.talkspurt_start && !state.is_gap_reported;
+            state.is_gap_reported = false;
+            (is_gap, format!("No packets for {:.0} ms", gap))
+        }
+        // depends on other streams, see `get_ssrc_change`
+        QualityRule::SsrcChange => return None,
+    };
+
+    // every gap is a separate event
+    let is_new = is_active && (!state.is_active || matches!(rule, QualityRule::Gap(_)));
+    state.is_active = is_active;
+    if !is_new {
+        return None;
+    }
+
+    Some(QualityEvent {
+        time: rtp_info.time,
+        packet_id: rtp_info.id,
+        rule: *rule,
+        description,
+    })
+}
+
+// gap is otherwise evaluated only when the next packet of the stream arrives,
+// so a stream that stopped would never fire, `time` is the time of the capture's
+// latest packet, streams with silence suppression are skipped, as it's not known
+// whether the next packet starts a talkspurt
+pub fn evaluate_idle(
+    stream: &Stream,
+    rule: &QualityRule,
+    state: &mut RuleState,
+    time: Duration,
+) -> Option<QualityEvent> {
+    let QualityRule::Gap(threshold) = *rule else {
+        return None;
+    };
+    let rtp_info = stream.rtp_packets.last()?;
+    let uses_silence_suppression = rtp_info.is_comfort_noise()
+        || stream
+            .talkspurt_stats
+            .as_ref()
+            .is_some_and(|stats| stats.talkspurts > 1);
+    if state.is_gap_reported || uses_silence_suppression {
+        return None;
+    }
+
+    let gap = time.saturating_sub(rtp_info.time).as_secs_f64() * 1000.0;
+    if gap <= threshold {
+        return None;
+    }
+    state.is_gap_reported = true;
+
+    Some(QualityEvent {
+        time: rtp_info.time + Duration::from_secs_f64(threshold / 1000.0),
+        packet_id: rtp_info.id,
+        rule: *rule,
+        description: format!("No packets for over {:.0} ms", threshold),
+    })
+}
+
+// previous stream with the same payload type on the transport of the new stream,
+// the one that received a packet most recently, simulcast layers and BUNDLE media
+// are told apart by their RID and MID
+pub fn get_ssrc_change(
+    streams: &HashMap<StreamKey, Stream>,
+    new_stream: &Stream,
+) -> Option<QualityEvent> {
+    let rtp_info = new_stream.rtp_packets.last()?;
+    let payload_type = rtp_info.packet.payload_type.id;
+
+    let previous = streams
+        .values()
+        .filter(|stream| {
+            stream.source_addr == new_stream.source_addr
+                && stream.destination_addr == new_stream.destination_addr
+                && stream.protocol == new_stream.protocol
+                && stream.ssrc != new_stream.ssrc
+                && stream.mid == new_stream.mid
+                && stream.rid == new_stream.rid
+        })
+        .filter_map(|stream| Some((stream, stream.rtp_packets.last()?)))
+        .filter(|(_, last)| last.packet.payload_type.id == payload_type)
+        .max_by_key(|(_, last)| last.time)
+        .map(|(stream, _)| stream)?;
+
+    Some(QualityEvent {
+        time: rtp_info.time,
+        packet_id: rtp_info.id,
+        rule: QualityRule::SsrcChange,
+        description: format!(
+            "SSRC changed from {:x} ({}) to {:x}",
+            previous.ssrc, previous.alias, new_stream.ssrc
+        ),
+    })
+}
+
+// fraction of packets lost in the last `window` seconds,
+// `None` until the stream lasts for the whole window,
+// packets not accounted in the sequence numbers (duplicates, unconfirmed jumps) are skipped
+fn get_window_loss(stream: &Stream, window: f64, window_start: &mut usize) -> Option<f64> {
+    let packets = &stream.rtp_packets;
+    let last = packets.last()?;
+    let get_age = |ix: usize| last.time.as_secs_f64() - packets[ix].time.as_secs_f64();
+
+    if get_age(0) < window {
+        return None;
+    }
+    while get_age(*window_start) > window {
+        *window_start += 1;
+    }
+
+    let received_sequence_numbers = stream.get_received_sequence_numbers();
+    let (first, last, received) = packets[*window_start..]
+        .iter()
+        .zip(*window_start..)
+        .filter(|(rtp_info, ix)| {
+            received_sequence_numbers.get(&rtp_info.extended_sequence_number) == Some(ix)
+        })
+        .map(|(rtp_info, _)| rtp_info.extended_sequence_number)
+        .fold((u32::MAX, 0, 0), |(first, last, received), extended| {
+            (first.min(extended), last.max(extended), received + 1)
+        });
+    if received == 0 {
+        return None;
+    }
+
+    let expected = last - first + 1;
+    Some(expected.saturating_sub(received) as f64 / expected as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streams::test_utils::{build_packet, build_rtp, capture_time};
+
+    // evaluates the rule after each packet, as the packets are captured every 20 ms
+    // with the given delay in ms from the given sequence number on
+    fn collect_events(lost: &[u16], delay: (u16, u64), rule: &QualityRule) -> Vec<QualityEvent> {
+        let mut packets = (0..300).filter(|sn| !lost.contains(sn)).map(|sn| {
+            let delay = if sn >= delay.0 { delay.1 } else { 0 };
+            let rtp = build_rtp(sn, false);
+            let packet = build_packet(sn as usize, capture_time(sn as u64 * 20 + delay), &rtp);
+            (packet, rtp)
+        });
+        let (packet, rtp) = packets.next().unwrap();
+        let mut stream = Stream::new(&packet, &rtp, "A".to_string(), None);
+        let mut state = RuleState::default();
+        let mut events: Vec<_> = evaluate(&stream, rule, &mut state).into_iter().collect();
+        for (packet, rtp) in packets {
+            stream.add_rtp_packet(&packet, &rtp);
+            events.extend(evaluate(&stream, rule, &mut state));
+        }
+        events
+    }
+
+    #[test]
+    fn raises_single_event_while_loss_is_above_threshold() {
+        let rule = QualityRule::Loss(5.0, 1.0);
+
+        // 6 of 50 packets within a second
+        let events = collect_events(&[100, 101, 102, 103, 104, 105], (0, 0), &rule);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].packet_id, 106);
+        assert!(collect_events(&[], (0, 0), &rule).is_empty());
+    }
+
+    #[test]
+    fn raises_event_for_each_gap() {
+        let rule = QualityRule::Gap(100.0);
+
+        let events = collect_events(&[], (100, 300), &rule);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].packet_id, 100);
+        assert!(collect_events(&[], (0, 0), &rule).is_empty());
+    }
+
+    #[test]
+    fn raises_idle_gap_once() {
+        let rule = QualityRule::Gap(100.0);
+        let rtp = build_rtp(0, false);
+        let stream = Stream::new(
+            &build_packet(0, capture_time(0), &rtp),
+            &rtp,
+            "A".to_string(),
+            None,
+        );
+        let mut state = RuleState::default();
+
+        assert!(evaluate_idle(&stream, &rule, &mut state, capture_time(100)).is_none());
+        let event = evaluate_idle(&stream, &rule, &mut state, capture_time(500)).unwrap();
+        assert_eq!(event.time, capture_time(100));
+        assert!(evaluate_idle(&stream, &rule, &mut state, capture_time(1000)).is_none());
+    }
+}
@@ -0,0 +1,230 @@
+use super::stream::Stream;
+use rtpeeker_common::rtp::payload_type::MediaType;
+use std::time::Duration;
+
+// packets with the same RTP timestamp, the last one has the marker bit set
+#[derive(Debug)]
+pub struct Frame {
+    pub timestamp: u32,
+    // extended sequence numbers
+    pub first_sequence_number: u32,
+    pub last_sequence_number: u32,
+    pub packets: usize,
+    pub bytes: usize, // payload only
+    // arrival of the first and the last packet
+    pub first_time: Duration,
+    pub last_time: Duration,
+    pub is_keyframe: bool,
+    // all of the packets were received, including the one with the marker
+    pub is_complete: bool,
+    // frame inter-arrival jitter in seconds, as in RFC 3550, section 6.4.1,
+    // but using arrival of the frame's last packet, if the clock rate is known
+    pub jitter: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct FrameStats {
+    pub frames: Vec<Frame>,
+    pub frame_rate: f64,
+    pub incomplete_frames: usize,
+    pub keyframes: usize,
+    // mean interval between keyframes in seconds
+    pub keyframe_interval: Option<f64>,
+    pub max_jitter: Option<f64>,
+}
+
+impl FrameStats {
+    pub fn build(stream: &Stream) -> Option<Self> {
+        if !matches!(stream.get_media_type(), Some(MediaType::Video)) {
+            return None;
+        }
+
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut ends_with_marker = true;
+        for (sequence_number, ix) in stream.get_received_sequence_numbers() {
+            let rtp_info = &stream.rtp_packets[*ix];
+            // retransmissions, FEC and others don't carry the media
+            if !rtp_info.follows_media_clock() {
+                continue;
+            }
+            let packet = &rtp_info.packet;
+
+            match frames.last_mut() {
+                Some(frame) if frame.timestamp == packet.timestamp && !ends_with_marker => {
+                    frame.is_complete &= *sequence_number == frame.last_sequence_number + 1;
+                    frame.last_sequence_number = *sequence_number;
+                    frame.packets += 1;
+                    frame.bytes += packet.payload_length;
+                    frame.first_time = frame.first_time.min(rtp_info.time);
+                    frame.last_time = frame.last_time.max(rtp_info.time);
+                    frame.is_keyframe |= rtp_info.is_keyframe();
+                }
+                previous => {
+                    // packets lost after the end of the previous frame belong to this one,
+                    // otherwise they are assumed to be the end of the previous frame
+                    let follows_previous = previous.is_none_or(|previous| {
+                        previous.is_complete &= ends_with_marker;
+                        !ends_with_marker || *sequence_number == previous.last_sequence_number + 1
+                    });
+                    frames.push(Frame {
+                        timestamp: packet.timestamp,
+                        first_sequence_number: *sequence_number,
+                        last_sequence_number: *sequence_number,
+                        packets: 1,
+                        bytes: packet.payload_length,
+                        first_time: rtp_info.time,
+                        last_time: rtp_info.time,
+                        is_keyframe: rtp_info.is_keyframe(),
+                        is_complete: follows_previous,
+                        jitter: None,
+                    });
+                }
+            }
+            ends_with_marker = packet.marker;
+        }
+        if let Some(frame) = frames.last_mut() {
+            frame.is_complete &= ends_with_marker;
+        }
+
+        if let Some(clock_rate) = stream.get_nominal_clock_rate() {
+            update_jitter(&mut frames, clock_rate);
+        }
+
+        let keyframe_times: Vec<_> = frames
+            .iter()
+            .filter(|frame| frame.is_keyframe)
+            .map(|frame| frame.first_time)
+            .collect();
+        let keyframe_interval = match (keyframe_times.first(), keyframe_times.last()) {
+            (Some(first), Some(last)) if keyframe_times.len() > 1 => {
+                Some(last.saturating_sub(*first).as_secs_f64() / (keyframe_times.len() - 1) as f64)
+            }
+            _ => None,
+        };
+
+        let duration = match (frames.first(), frames.last()) {
+            (Some(first), Some(last)) => last.first_time.saturating_sub(first.first_time),
+            _ => Duration::ZERO,
+        };
+        let frame_rate = if duration.is_zero() {
+            0.0
+        } else {
+            (frames.len() - 1) as f64 / duration.as_secs_f64()
+        };
+
+        Some(Self {
+            frame_rate,
+            incomplete_frames: frames.iter().filter(|frame| !frame.is_complete).count(),
+            keyframes: keyframe_times.len(),
+            keyframe_interval,
+            max_jitter: frames
+                .iter()
+                .filter_map(|frame| frame.jitter)
+                .max_by(f64::total_cmp),
+            frames,
+        })
+    }
+
+    // frame size in bytes at the given percentile
+    pub fn get_frame_size(&self, percentile: f64) -> usize {
+        let mut sizes: Vec<_> = self.frames.iter().map(|frame| frame.bytes).collect();
+        if sizes.is_empty() {
+            return 0;
+        }
+        sizes.sort_unstable();
+        let ix = ((sizes.len() - 1) as f64 * percentile).round() as usize;
+        sizes[ix]
+    }
+
+    pub fn get_mean_frame_size(&self) -> f64 {
+        if self.frames.is_empty() {
+            return 0.0;
+        }
+        let bytes: usize = self.frames.iter().map(|frame| frame.bytes).sum();
+        bytes as f64 / self.frames.len() as f64
+    }
+}
+
+fn update_jitter(frames: &mut [Frame], clock_rate: u32) {
+    let mut jitter = 0.0;
+    for ix in 1..frames.len() {
+        let (previous, frame) = (&frames[ix - 1], &frames[ix]);
+        let arrival_diff = frame.last_time.as_secs_f64() - previous.last_time.as_secs_f64();
+        let timestamp_diff = frame.timestamp.wrapping_sub(previous.timestamp) as i32 as f64;
+        let diff = arrival_diff - timestamp_diff / clock_rate as f64;
+        jitter += (diff.abs() - jitter) / 16.0;
+        frames[ix].jitter = Some(jitter);
+    }
+    if let Some(frame) = frames.first_mut() {
+        frame.jitter = Some(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streams::test_utils::{build_rtp, build_stream, capture_time};
+    use rtpeeker_common::rtp::payload_type::PayloadType;
+
+    // JPEG video with frames of 3 packets, every 30 ms,
+    // the first ones with the given delay in ms
+    fn build_video_stream(frames: u16, lost: &[u16], delays: &[u64]) -> Stream {
+        let packets: Vec<_> = (0..frames * 3)
+            .filter(|sn| !lost.contains(sn))
+            .map(|sn| {
+                let frame = sn / 3;
+                let mut rtp = build_rtp(sn, sn % 3 == 2);
+                rtp.payload_type = PayloadType::new(26);
+                rtp.timestamp = frame as u32 * 2700;
+                let delay = delays.get(frame as usize).copied().unwrap_or(0);
+                (capture_time(frame as u64 * 30 + delay), rtp)
+            })
+            .collect();
+        build_stream(&packets)
+    }
+
+    #[test]
+    fn marks_frames_with_lost_packets_incomplete() {
+        // lost middle packet of the second frame, marker packet of the third one
+        // and the first packet of the fifth one
+        let stream = build_video_stream(6, &[4, 8, 12], &[]);
+
+        let stats = FrameStats::build(&stream).unwrap();
+
+        let frames: Vec<_> = stats
+            .frames
+            .iter()
+            .map(|frame| (frame.timestamp, frame.packets, frame.is_complete))
+            .collect();
+        assert_eq!(
+            frames,
+            vec![
+                (0, 3, true),
+                (2700, 2, false),
+                (5400, 2, false),
+                (8100, 3, true),
+                (10800, 2, false),
+                (13500, 3, true),
+            ]
+        );
+        assert_eq!(stats.incomplete_frames, 3);
+    }
+
+    #[test]
+    fn computes_frame_jitter() {
+        // the third frame arrives 16 ms late
+        let stream = build_video_stream(4, &[], &[0, 0, 16]);
+
+        let stats = FrameStats::build(&stream).unwrap();
+
+        let jitters: Vec<_> = stats
+            .frames
+            .iter()
+            .map(|frame| frame.jitter.unwrap())
+            .collect();
+        assert!((jitters[1] - 0.0).abs() < 1e-9);
+        assert!((jitters[2] - 0.001).abs() < 1e-9);
+        assert!((jitters[3] - 0.001_937_5).abs() < 1e-9);
+        assert!((stats.frame_rate - 1.0 / 0.03).abs() < 1e-6);
+    }
+}
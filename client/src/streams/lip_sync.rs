@@ -0,0 +1,96 @@
+use super::stream::Stream;
+use crate::utils::ntp_to_f64;
+use rtpeeker_common::StreamKey;
+use std::collections::BTreeMap;
+
+// detectability thresholds of the A/V offset in seconds, see ITU-R BT.1359
+pub const MAX_AUDIO_LEAD: f64 = 0.045;
+pub const MAX_AUDIO_LAG: f64 = 0.125;
+// streams are compared in windows of arrival time using the least delayed packet,
+// so that the jitter and sending video frames in bursts don't count as the offset
+const SKEW_WINDOW: f64 = 1.0;
+
+// audio and video streams of a single participant (RTCP CNAME)
+#[derive(Debug)]
+pub struct LipSyncStats {
+    pub cname: String,
+    pub audio_key: StreamKey,
+    pub video_key: StreamKey,
+    // start of the window in seconds (arrival time) and the A/V offset in seconds,
+    // positive if the audio is presented later than the corresponding video
+    pub skews: Vec<(f64, f64)>,
+}
+
+impl LipSyncStats {
+    pub fn build(
+        cname: String,
+        (audio_key, audio): (StreamKey, &Stream),
+        (video_key, video): (StreamKey, &Stream),
+    ) -> Option<Self> {
+        let video_delays = get_min_delays(video);
+        let skews: Vec<_> = get_min_delays(audio)
+            .into_iter()
+            .filter_map(|(window, audio_delay)| {
+                let video_delay = video_delays.get(&window)?;
+                Some((window as f64 * SKEW_WINDOW, audio_delay - video_delay))
+            })
+            .collect();
+
+        if skews.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            cname,
+            audio_key,
+            video_key,
+            skews,
+        })
+    }
+
+    pub fn get_mean_skew(&self) -> f64 {
+        let sum: f64 = self.skews.iter().map(|(_, skew)| skew).sum();
+        sum / self.skews.len() as f64
+    }
+
+    // the offset furthest from zero
+    pub fn get_max_skew(&self) -> f64 {
+        self.skews
+            .iter()
+            .map(|(_, skew)| *skew)
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .unwrap()
+    }
+
+    pub fn get_out_of_sync_fraction(&self) -> f64 {
+        let out_of_sync = self
+            .skews
+            .iter()
+            .filter(|(_, skew)| !is_in_sync(*skew))
+            .count();
+        out_of_sync as f64 / self.skews.len() as f64
+    }
+}
+
+pub fn is_in_sync(skew: f64) -> bool {
+    (-MAX_AUDIO_LEAD..=MAX_AUDIO_LAG).contains(&skew)
+}
+
+// delay between the sender's NTP time and the arrival, includes the unknown
+// offset between sender's and capturing host's clocks, which is the same for all streams
+fn get_min_delays(stream: &Stream) -> BTreeMap<u64, f64> {
+    let mut delays = BTreeMap::new();
+    for rtp_info in &stream.rtp_packets {
+        let Some(ntp_time) = rtp_info.ntp_time.filter(|_| rtp_info.follows_media_clock()) else {
+            continue;
+        };
+        let time = rtp_info.time.as_secs_f64();
+        let delay = time - ntp_to_f64(ntp_time);
+        let window = (time / SKEW_WINDOW) as u64;
+        delays
+            .entry(window)
+            .and_modify(|min_delay: &mut f64| *min_delay = min_delay.min(delay))
+            .or_insert(delay);
+    }
+    delays
+}
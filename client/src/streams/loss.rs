@@ -0,0 +1,126 @@
+use super::stream::Stream;
+use std::time::Duration;
+
+// minimal number of consecutive received packets that ends a burst, see RFC 3611, section 4.7.2
+const MIN_GAP_LENGTH: u32 = 16;
+
+// run of consecutive lost packets
+#[derive(Debug)]
+pub struct LossEvent {
+    pub first_sequence_number: u32, // extended
+    pub length: usize,
+    // arrival time of the last packet received before the loss
+    pub time: Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct BurstStats {
+    pub bursts: usize,
+    // fraction of packets lost within bursts and within gaps
+    pub burst_density: f64,
+    pub gap_density: f64,
+    pub mean_burst_length: f64, // in packets
+    pub longest_loss_run: usize,
+    pub events: Vec<LossEvent>,
+}
+
+impl BurstStats {
+    pub fn build(stream: &Stream) -> Self {
+        let received = stream.get_received_sequence_numbers();
+        let events: Vec<_> = received
+            .iter()
+            .zip(received.keys().skip(1))
+            .filter(|((sequence_number, _), next)| *next - *sequence_number > 1)
+            .map(|((sequence_number, ix), next)| LossEvent {
+                first_sequence_number: sequence_number + 1,
+                length: (next - sequence_number - 1) as usize,
+                time: stream.rtp_packets[*ix].time,
+            })
+            .collect();
+
+        // burst is a sequence of losses separated by less than `MIN_GAP_LENGTH` received
+        // packets, a single isolated loss belongs to the gap,
+        // bursts are kept as their first and last sequence number and the number of lost packets
+        let mut bursts: Vec<(u32, u32, usize)> = Vec::new();
+        let mut current: Option<(u32, u32, usize)> = None;
+        for event in &events {
+            let first = event.first_sequence_number;
+            let last = first + event.length as u32 - 1;
+            current = match current {
+                Some((start, end, lost)) if first - end - 1 < MIN_GAP_LENGTH => {
+                    Some((start, last, lost + event.length))
+                }
+                previous => {
+                    bursts.extend(previous.filter(|(_, _, lost)| *lost > 1));
+                    Some((first, last, event.length))
+                }
+            };
+        }
+        bursts.extend(current.filter(|(_, _, lost)| *lost > 1));
+
+        let expected = stream.get_expected_count();
+        let lost: usize = events.iter().map(|event| event.length).sum();
+        let burst_packets: usize = bursts
+            .iter()
+            .map(|(start, end, _)| (end - start) as usize + 1)
+            .sum();
+        let burst_lost: usize = bursts.iter().map(|(_, _, lost)| lost).sum();
+        let gap_packets = expected - burst_packets;
+
+        Self {
+            bursts: bursts.len(),
+            burst_density: get_fraction(burst_lost, burst_packets),
+            gap_density: get_fraction(lost - burst_lost, gap_packets),
+            mean_burst_length: get_fraction(burst_packets, bursts.len()),
+            longest_loss_run: events.iter().map(|event| event.length).max().unwrap_or(0),
+            events,
+        }
+    }
+}
+
+fn get_fraction(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        return 0.0;
+    }
+    numerator as f64 / denominator as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streams::test_utils::build_audio_stream;
+
+    #[test]
+    fn separates_bursts_from_gaps() {
+        // isolated losses of 10 and 80, burst of 40, 41 and 43
+        let lost = [10, 40, 41, 43, 80];
+        let stream = build_audio_stream((0..100).filter(|sn| !lost.contains(sn)));
+
+        let stats = BurstStats::build(&stream);
+
+        assert_eq!(stats.bursts, 1);
+        assert_eq!(stats.burst_density, 0.75);
+        assert_eq!(stats.gap_density, 2.0 / 96.0);
+        assert_eq!(stats.mean_burst_length, 4.0);
+        assert_eq!(stats.longest_loss_run, 2);
+        let runs: Vec<_> = stats
+            .events
+            .iter()
+            .map(|event| (event.first_sequence_number % (1 << 16), event.length))
+            .collect();
+        assert_eq!(runs, vec![(10, 1), (40, 2), (43, 1), (80, 1)]);
+    }
+
+    #[test]
+    fn ends_burst_after_min_gap_length() {
+        // losses separated by exactly `MIN_GAP_LENGTH` received packets
+        let lost = [20, 37];
+        let stream = build_audio_stream((0..60).filter(|sn| !lost.contains(sn)));
+
+        let stats = BurstStats::build(&stream);
+
+        assert_eq!(stats.bursts, 0);
+        assert_eq!(stats.burst_density, 0.0);
+        assert_eq!(stats.gap_density, 2.0 / 60.0);
+    }
+}
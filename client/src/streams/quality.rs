@@ -0,0 +1,242 @@
+use super::stream::Stream;
+use rtpeeker_common::rtp::payload_type::MediaType;
+use std::collections::BTreeMap;
+
+// length of windows in seconds to find degraded periods
+pub const QUALITY_WINDOW: f64 = 5.0;
+// default value of R0 - Is, see ITU-T G.107, section 7.7
+const DEFAULT_R: f64 = 93.2;
+// see ITU-T G.107, Appendix I (simplified delay impairment)
+const DELAY_THRESHOLD: f64 = 177.3; // in ms
+
+#[derive(Debug, Clone, Copy)]
+pub struct QualityScore {
+    pub r_factor: f64,
+    pub mos: f64,
+    // inputs the score was computed from
+    pub loss: f64,
+    pub burst_ratio: f64,
+    pub jitter: f64,      // in seconds
+    pub rtt: Option<f64>, // in seconds
+}
+
+impl QualityScore {
+    // E-model, see ITU-T G.107
+    fn build(codec: &str, loss: &LossSummary, jitter: f64, rtt: Option<f64>) -> Self {
+        let (ie, bpl) = get_codec_impairment(codec);
+        let loss_percent = loss.get_fraction() * 100.0;
+        let burst_ratio = loss.get_burst_ratio();
+        let ie_eff = ie + (95.0 - ie) * loss_percent / (loss_percent / burst_ratio + bpl);
+
+        // one-way delay, assuming jitter buffer twice as long as the jitter
+        let delay = (rtt.unwrap_or(0.0) / 2.0 + 2.0 * jitter) * 1000.0;
+        let mut id = 0.024 * delay;
+        if delay > DELAY_THRESHOLD {
+            id += 0.11 * (delay - DELAY_THRESHOLD);
+        }
+
+        let r_factor = DEFAULT_R - id - ie_eff;
+        Self {
+            r_factor,
+            mos: r_to_mos(r_factor),
+            loss: loss.get_fraction(),
+            burst_ratio,
+            jitter,
+            rtt,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct QualityWindow {
+    // in seconds from the stream's first packet
+    pub start: f64,
+    pub score: QualityScore,
+}
+
+pub fn get_stream_quality(stream: &Stream) -> Option<QualityScore> {
+    let codec = get_audio_codec(stream)?;
+    let loss = LossSummary::build(stream.get_received_sequence_numbers().keys().copied());
+    let jitter = stream.get_mean_jitter().unwrap_or(0.0);
+    Some(QualityScore::build(&codec, &loss, jitter, get_rtt(stream)))
+}
+
+pub fn get_quality_history(stream: &Stream) -> Vec<QualityWindow> {
+    let Some(codec) = get_audio_codec(stream) else {
+        return Vec::new();
+    };
+    let rtt = get_rtt(stream);
+    let first_time = stream.rtp_packets.first().unwrap().time;
+
+    // extended sequence numbers and jitter values of each window,
+    // duplicates and packets after an unconfirmed jump are not counted as received
+    let received_sequence_numbers = stream.get_received_sequence_numbers();
+    let mut windows: BTreeMap<u64, (Vec<u32>, Vec<f64>)> = BTreeMap::new();
+    for (ix, rtp_info) in stream.rtp_packets.iter().enumerate() {
+        let offset = rtp_info.time.saturating_sub(first_time).as_secs_f64();
+        let (sequence_numbers, jitters) =
+            windows.entry((offset / QUALITY_WINDOW) as u64).or_default();
+        let extended = rtp_info.extended_sequence_number;
+        if received_sequence_numbers.get(&extended) == Some(&ix) {
+            sequence_numbers.push(extended);
+        }
+        jitters.extend(rtp_info.jitter);
+    }
+
+    windows
+        .into_iter()
+        .map(|(window, (mut sequence_numbers, jitters))| {
+            sequence_numbers.sort_unstable();
+            let loss = LossSummary::build(sequence_numbers.into_iter());
+            let jitter = if jitters.is_empty() {
+                0.0
+            } else {
+                jitters.iter().sum::<f64>() / jitters.len() as f64
+            };
+            QualityWindow {
+                start: window as f64 * QUALITY_WINDOW,
+                score: QualityScore::build(&codec, &loss, jitter, rtt),
+            }
+        })
+        .collect()
+}
+
+// see ITU-T G.107, Annex B
+pub fn r_to_mos(r_factor: f64) -> f64 {
+    if r_factor <= 0.0 {
+        1.0
+    } else if r_factor >= 100.0 {
+        4.5
+    } else {
+        1.0 + 0.035 * r_factor + r_factor * (r_factor - 60.0) * (100.0 - r_factor) * 7e-6
+    }
+}
+
+fn get_audio_codec(stream: &Stream) -> Option<String> {
+    let payload_type = stream.get_media_payload_type()?;
+    if !matches!(payload_type.media_type, MediaType::Audio) {
+        return None;
+    }
+    Some(payload_type.name)
+}
+
+fn get_rtt(stream: &Stream) -> Option<f64> {
+    stream
+        .get_sender_rtt_stats()
+        .or_else(|| stream.get_capture_rtt_stats())
+        .map(|stats| stats.mean)
+}
+
+// equipment impairment factor and packet-loss robustness factor,
+// see ITU-T G.113, Appendix I, unknown codecs are treated as G.711 with PLC
+fn get_codec_impairment(codec: &str) -> (f64, f64) {
+    match codec.to_ascii_uppercase().as_str() {
+        "G729" => (11.0, 19.0),
+        "G723" => (15.0, 16.1),
+        "ILBC" => (11.0, 32.0),
+        "AMR" => (5.0, 10.0),
+        _ => (0.0, 25.1),
+    }
+}
+
+struct LossSummary {
+    expected: usize,
+    lost: usize,
+    // runs of consecutive lost packets
+    loss_runs: usize,
+}
+
+impl LossSummary {
+    // sequence numbers have to be sorted and unique
+    fn build(mut sequence_numbers: impl Iterator<Item = u32>) -> Self {
+        let mut summary = Self {
+            expected: 0,
+            lost: 0,
+            loss_runs: 0,
+        };
+        let Some(mut previous) = sequence_numbers.next() else {
+            return summary;
+        };
+
+        summary.expected = 1;
+        for sequence_number in sequence_numbers {
+            let gap = (sequence_number - previous - 1) as usize;
+            if gap > 0 {
+                summary.lost += gap;
+                summary.loss_runs += 1;
+            }
+            summary.expected += gap + 1;
+            previous = sequence_number;
+        }
+        summary
+    }
+
+    fn get_fraction(&self) -> f64 {
+        if self.expected == 0 {
+            return 0.0;
+        }
+        self.lost as f64 / self.expected as f64
+    }
+
+    // ratio of the mean loss run length to the one expected for random loss,
+    // using the 2-state Markov model, see ITU-T G.113, Appendix I
+    fn get_burst_ratio(&self) -> f64 {
+        let received = self.expected - self.lost;
+        if self.lost == 0 || received == 0 {
+            return 1.0;
+        }
+        // probabilities of transitions from received to lost and back
+        let p = self.loss_runs as f64 / received as f64;
+        let q = self.loss_runs as f64 / self.lost as f64;
+        1.0 / (p + q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streams::test_utils::build_audio_stream;
+
+    #[test]
+    fn scores_lossless_g711_with_default_r() {
+        let stream = build_audio_stream(0..100);
+
+        let score = get_stream_quality(&stream).unwrap();
+
+        assert!((score.r_factor - 93.2).abs() < 1e-9);
+        assert!((score.mos - 4.41).abs() < 0.005);
+        assert_eq!(score.loss, 0.0);
+        assert_eq!(score.burst_ratio, 1.0);
+    }
+
+    #[test]
+    fn scores_random_loss_of_g711() {
+        // every 20th packet lost, which is less bursty than random loss
+        let stream = build_audio_stream((0..1000).filter(|sn| sn % 20 != 10));
+
+        let score = get_stream_quality(&stream).unwrap();
+
+        // BurstR = 1 / (50 / 950 + 50 / 50), Ie-eff = 95 * 5 / (5 / BurstR + 25.1)
+        assert!((score.loss - 0.05).abs() < 1e-9);
+        assert!((score.burst_ratio - 0.95).abs() < 1e-9);
+        let ie_eff = 95.0 * 5.0 / (5.0 / 0.95 + 25.1);
+        assert!((score.r_factor - (93.2 - ie_eff)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn scores_each_window_separately() {
+        // 20 seconds of audio with 10 packets lost in the third window only,
+        // the duplicate in the first window doesn't make up for any loss
+        let mut sequence_numbers: Vec<_> =
+            (0..1000).filter(|sn| !(600..610).contains(sn)).collect();
+        sequence_numbers.insert(101, 100);
+        let stream = build_audio_stream(sequence_numbers);
+
+        let history = get_quality_history(&stream);
+
+        let starts: Vec<_> = history.iter().map(|window| window.start).collect();
+        assert_eq!(starts, vec![0.0, 5.0, 10.0, 15.0]);
+        let losses: Vec<_> = history.iter().map(|window| window.score.loss).collect();
+        assert_eq!(losses, vec![0.0, 0.0, 10.0 / 250.0, 0.0]);
+    }
+}
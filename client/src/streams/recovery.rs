@@ -0,0 +1,244 @@
+use super::stream::Stream;
+use rtpeeker_common::rtp::fec::FecPayload;
+use rtpeeker_common::rtp::payload::PayloadInfo;
+use rtpeeker_common::rtp::sequence::extend_sequence_number;
+use std::collections::HashSet;
+
+#[derive(Debug, Default, Clone)]
+pub struct RecoveryStats {
+    pub lost: usize,
+    pub recoverable_by_fec: usize,
+    pub recoverable_by_red: usize,
+    pub recoverable: usize,
+    pub fec_packets: usize,
+    pub red_packets: usize,
+}
+
+impl RecoveryStats {
+    // FEC payloads come with the ids of the packets carrying them
+    pub fn build<'a>(
+        stream: &'a Stream,
+        fec_payloads: impl Iterator<Item = (usize, &'a FecPayload)>,
+    ) -> Self {
+        let received = stream.get_received_sequence_numbers();
+        let lost = stream.get_lost_sequence_numbers();
+        let extender = SequenceExtender::new(stream);
+
+        // RED blocks are identified by the timestamp offset from the packet carrying them,
+        // the timestamp of a lost packet is interpolated from the closest received packets
+        let mut red_packets = 0;
+        let mut red_timestamps = HashSet::new();
+        for rtp in &stream.rtp_packets {
+            let Some(PayloadInfo::Red(red)) = &rtp.payload_info else {
+                continue;
+            };
+            red_packets += 1;
+            red_timestamps.extend(red.blocks.iter().filter_map(|block| {
+                let offset = block.timestamp_offset?;
+                Some(rtp.packet.timestamp.wrapping_sub(offset as u32))
+            }));
+        }
+
+        let mut recovered_by_red = HashSet::new();
+        let neighbours = received.iter().zip(received.iter().skip(1));
+        for ((&prev_sequence_number, &prev_ix), (&next_sequence_number, &next_ix)) in neighbours {
+            let gap = next_sequence_number - prev_sequence_number;
+            if gap < 2 {
+                continue;
+            }
+            let prev_timestamp = stream.rtp_packets[prev_ix].packet.timestamp;
+            let next_timestamp = stream.rtp_packets[next_ix].packet.timestamp;
+            let step = next_timestamp.wrapping_sub(prev_timestamp) / gap;
+            for distance in 1..gap {
+                let timestamp = prev_timestamp.wrapping_add(step * distance);
+                let sequence_number = prev_sequence_number + distance;
+                if red_timestamps.contains(&timestamp) && lost.contains(&sequence_number) {
+                    recovered_by_red.insert(sequence_number);
+                }
+            }
+        }
+
+        // FEC packet recovers a packet if it's the only missing packet of those it protects,
+        // recovered packets can then be used with other FEC packets
+        let protected_sequence_numbers: Vec<Vec<_>> = fec_payloads
+            .map(|(id, fec)| {
+                fec.protected_sequence_numbers
+                    .iter()
+                    .map(|sequence_number| extender.extend(*sequence_number, id))
+                    .collect()
+            })
+            .collect();
+        let mut recovered_by_fec = HashSet::new();
+        loop {
+            let mut is_changed = false;
+            for protected in &protected_sequence_numbers {
+                let mut missing = protected
+                    .iter()
+                    .filter(|sn| !received.contains_key(*sn) && !recovered_by_fec.contains(*sn));
+                let (Some(&sequence_number), None) = (missing.next(), missing.next()) else {
+                    continue;
+                };
+                if lost.contains(&sequence_number) {
+                    recovered_by_fec.insert(sequence_number);
+                    is_changed = true;
+                }
+            }
+            if !is_changed {
+                break;
+            }
+        }
+
+        Self {
+            lost: lost.len(),
+            recoverable_by_fec: recovered_by_fec.len(),
+            recoverable_by_red: recovered_by_red.len(),
+            recoverable: recovered_by_fec.union(&recovered_by_red).count(),
+            fec_packets: protected_sequence_numbers.len(),
+            red_packets,
+        }
+    }
+
+    pub fn has_redundancy(&self) -> bool {
+        self.fec_packets > 0 || self.red_packets > 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetransmissionStats {
+    pub rtx_alias: String,
+    pub rtx_ssrc: u32,
+    pub retransmitted: usize,
+    pub lost_after_retransmission: usize,
+}
+
+impl RetransmissionStats {
+    pub fn build(stream: &Stream, rtx_stream: &Stream) -> Self {
+        let lost = stream.get_lost_sequence_numbers();
+        let extender = SequenceExtender::new(stream);
+        let retransmitted: HashSet<_> = rtx_stream
+            .get_original_sequence_numbers()
+            .into_iter()
+            .map(|(id, sequence_number)| extender.extend(sequence_number, id))
+            .collect();
+
+        Self {
+            rtx_alias: rtx_stream.alias.clone(),
+            rtx_ssrc: rtx_stream.ssrc,
+            retransmitted: retransmitted.len(),
+            lost_after_retransmission: lost.difference(&retransmitted).count(),
+        }
+    }
+}
+
+// sequence numbers carried in the payload (by FEC or RTX) are extended relative
+// to the highest sequence number of the stream received before the carrying packet
+struct SequenceExtender {
+    // packet ids with the highest extended sequence number received up to that packet
+    extended_max: Vec<(usize, u32)>,
+}
+
+impl SequenceExtender {
+    fn new(stream: &Stream) -> Self {
+        let mut by_ix = vec![None; stream.rtp_packets.len()];
+        for (extended, ix) in stream.get_received_sequence_numbers() {
+            by_ix[*ix] = Some(*extended);
+        }
+
+        let mut max = None;
+        let extended_max = stream
+            .rtp_packets
+            .iter()
+            .zip(by_ix)
+            .filter_map(|(rtp, extended)| {
+                max = max.max(extended);
+                Some((rtp.id, max?))
+            })
+            .collect();
+
+        Self { extended_max }
+    }
+
+    fn extend(&self, sequence_number: u16, id: usize) -> u32 {
+        let ix = self
+            .extended_max
+            .partition_point(|(packet_id, _)| *packet_id <= id);
+        let reference = self
+            .extended_max
+            .get(ix.saturating_sub(1))
+            .map_or(0, |(_, extended)| *extended);
+        extend_sequence_number(sequence_number, reference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streams::test_utils::{build_audio_stream, build_rtp, build_stream, capture_time};
+    use rtpeeker_common::rtp::fec::FecScheme;
+    use rtpeeker_common::rtp::red::{RedBlock, RedPayload};
+
+    fn build_fec(protected_sequence_numbers: Vec<u16>) -> FecPayload {
+        FecPayload {
+            scheme: FecScheme::Ulpfec,
+            protected_ssrc: None,
+            sequence_number_base: protected_sequence_numbers[0],
+            protected_sequence_numbers,
+            timestamp_recovery: 0,
+            length_recovery: 0,
+        }
+    }
+
+    #[test]
+    fn counts_losses_recoverable_by_red_and_chained_fec() {
+        // 3, 5, 6 and 8 are lost
+        let mut stream = build_audio_stream([0, 1, 2, 4, 7, 9]);
+        // packet 4 carries the previous packet in a redundant block
+        let red = RedPayload {
+            blocks: vec![
+                RedBlock {
+                    payload_type: 0,
+                    timestamp_offset: Some(160),
+                    offset: 4,
+                    length: 160,
+                },
+                RedBlock {
+                    payload_type: 0,
+                    timestamp_offset: None,
+                    offset: 164,
+                    length: 160,
+                },
+            ],
+            primary: None,
+        };
+        stream.rtp_packets[3].payload_info = Some(PayloadInfo::Red(red));
+        // the first FEC packet recovers 5 only after the second one recovers 6
+        // and both are captured after the media packets
+        let fec_payloads = [build_fec(vec![5, 6]), build_fec(vec![6, 7])];
+        let fec_payloads = fec_payloads.iter().map(|fec| (6, fec));
+
+        let stats = RecoveryStats::build(&stream, fec_payloads);
+
+        assert_eq!(stats.lost, 4);
+        assert_eq!(stats.recoverable_by_red, 1);
+        assert_eq!(stats.recoverable_by_fec, 2);
+        assert_eq!(stats.recoverable, 3);
+        assert_eq!(stats.red_packets, 1);
+        assert_eq!(stats.fec_packets, 2);
+    }
+    #[test]
+    fn extends_protected_sequence_numbers_across_wraparound() {
+        // 0 is lost right after the wraparound
+        let packets: Vec<_> = [65534, 65535, 1]
+            .into_iter()
+            .zip([0, 20, 60])
+            .map(|(sn, millis)| (capture_time(millis), build_rtp(sn, false)))
+            .collect();
+        let stream = build_stream(&packets);
+        let fec = build_fec(vec![65535, 0]);
+
+        let stats = RecoveryStats::build(&stream, [(3, &fec)].into_iter());
+
+        assert_eq!(stats.lost, 1);
+        assert_eq!(stats.recoverable_by_fec, 1);
+    }
+}
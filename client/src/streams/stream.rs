@@ -1,12 +1,57 @@
-use crate::utils::ntp_to_f64;
+use super::events::{self, QualityEvent, RuleState};
+use super::frames::FrameStats;
+use super::loss::BurstStats;
+use super::quality::{self, QualityScore, QualityWindow};
+use crate::utils::{ntp_to_f64, time_to_ntp};
+use rtpeeker_common::flow::is_consistent;
 use rtpeeker_common::packet::TransportProtocol;
-use rtpeeker_common::rtcp::{source_description::SdesType, SourceDescription};
-use rtpeeker_common::rtp::payload_type::PayloadType;
-use rtpeeker_common::{Packet, RtcpPacket, RtpPacket, Sdp};
+use rtpeeker_common::rtcp::{
+    source_description::SdesType, ReceptionReport, SenderReport, SourceDescription,
+};
+use rtpeeker_common::rtp::dependency_descriptor::{
+    DependencyDescriptor, DEPENDENCY_DESCRIPTOR_URI,
+};
+use rtpeeker_common::rtp::mpeg_ts::{NULL_PID, PCR_CLOCK_RATE, PCR_MODULUS, TS_PACKET_LENGTH};
+use rtpeeker_common::rtp::payload::{Layer, PayloadInfo};
+use rtpeeker_common::rtp::payload_type::{MediaType, PayloadType};
+use rtpeeker_common::rtp::sequence::{SequenceTracker, SequenceUpdate};
+use rtpeeker_common::rtp::stream_id::{parse_sdes_value, MID_URI, REPAIRED_RID_URI, RID_URI};
+use rtpeeker_common::{Packet, QualityRule, RtcpPacket, RtpPacket, Sdp};
+use std::cell::OnceCell;
 use std::cmp::{max, min};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::net::SocketAddr;
 use std::time::Duration;
 
+// reordered packets arriving later than that would be discarded by a typical jitter buffer
+const LATE_PACKET_DELAY: Duration = Duration::from_millis(100);
+// maximal interval between PAT or PMT repetitions, see TR 101 290, section 5.2.1
+const MAX_PSI_INTERVAL: Duration = Duration::from_millis(500);
+// sender reports with NTP time progressing differently than the arrival time
+// by more seconds than that are bogus
+const MAX_NTP_DEVIATION: f64 = 1.0;
+// maximal deviation of the clock rate between two sender reports from the nominal one
+const MAX_CLOCK_RATE_DEVIATION: f64 = 0.01;
+// minimal interval in seconds between sender reports to estimate the clock rate
+const MIN_CLOCK_RATE_INTERVAL: f64 = 1.0;
+const NTP_UNITS_PER_SECOND: f64 = 4_294_967_296.0;
+// interval of arrival times in which the clock rate of a dynamic payload type
+// is inferred from RTP timestamps, before the timestamp difference could wrap around
+const MIN_CLOCK_RATE_INFERENCE_TIME: Duration = Duration::from_secs(1);
+const MAX_CLOCK_RATE_INFERENCE_TIME: Duration = Duration::from_secs(60);
+const COMMON_CLOCK_RATES: [u32; 7] = [8000, 16000, 24000, 32000, 44100, 48000, 90000];
+// relative error of the inferred clock rate to snap it to a common value
+const MAX_CLOCK_RATE_ERROR: f64 = 0.1;
+// LSR and DLSR are in the middle 32 bits of the NTP format
+const DLSR_UNITS_PER_SECOND: f64 = 65_536.0;
+// RTT computed using the capture host's clock above that means
+// that it isn't synchronized with the sender's clock
+const MAX_SENDER_RTT: f64 = 10.0;
+// reported and observed loss fraction or jitter (in seconds) differing
+// by more than that are considered divergent
+const MAX_LOSS_DIVERGENCE: f64 = 0.01;
+const MAX_JITTER_DIVERGENCE: f64 = 0.005;
+
 #[derive(Debug)]
 pub struct RtcpInfo {
     pub packet: RtcpPacket,
@@ -33,9 +78,240 @@ pub struct RtpInfo {
     pub time_delta: Duration,
     pub jitter: Option<f64>,
     pub prev_lost: bool,
+    pub extended_sequence_number: u32,
     pub bytes: usize,
     pub bitrate: usize,     // in the last second, kbps
     pub packet_rate: usize, // packets/s
+    pub payload_info: Option<PayloadInfo>,
+    pub dependency_descriptor: Option<DependencyDescriptor>,
+    pub talkspurt: Option<usize>, // None for non-speech packets
+    pub talkspurt_start: bool,
+}
+
+impl RtpInfo {
+    // payload info of the primary block if the packet is RED
+    pub fn get_primary_payload_info(&self) -> Option<&PayloadInfo> {
+        self.payload_info
+            .as_ref()
+            .map(|payload_info| payload_info.get_primary())
+    }
+
+    pub fn is_telephone_event(&self) -> bool {
+        matches!(
+            self.get_primary_payload_info(),
+            Some(PayloadInfo::TelephoneEvent(_))
+        )
+    }
+
+    pub fn is_comfort_noise(&self) -> bool {
+        matches!(
+            self.get_primary_payload_info(),
+            Some(PayloadInfo::ComfortNoise(_))
+        )
+    }
+
+    pub fn is_fec(&self) -> bool {
+        self.payload_info
+            .as_ref()
+            .is_some_and(|payload_info| payload_info.is_fec())
+    }
+
+    pub fn is_retransmission(&self) -> bool {
+        matches!(self.payload_info, Some(PayloadInfo::Rtx(_)))
+    }
+
+    // whether RTP timestamps of the packet follow the regular media sampling
+    pub fn follows_media_clock(&self) -> bool {
+        !self.is_telephone_event()
+            && !self.is_comfort_noise()
+            && !self.is_fec()
+            && !self.is_retransmission()
+    }
+
+    pub fn is_keyframe(&self) -> bool {
+        let payload_keyframe = self
+            .payload_info
+            .as_ref()
+            .is_some_and(|payload_info| payload_info.is_keyframe());
+        let descriptor_keyframe = self
+            .dependency_descriptor
+            .as_ref()
+            .is_some_and(|descriptor| descriptor.is_keyframe());
+
+        payload_keyframe || descriptor_keyframe
+    }
+}
+
+#[derive(Debug)]
+pub struct LayerStats {
+    pub bytes: usize,
+    pub frames: usize,
+    last_timestamp: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct OpusStats {
+    pub packets: usize,
+    pub dtx_packets: usize,
+    pub fec_packets: usize,
+    // sum of packet durations of non-DTX packets, in ms
+    sum_packet_duration: f64,
+}
+
+impl OpusStats {
+    pub fn get_mean_packetization_time(&self) -> Option<f64> {
+        let packets = self.packets - self.dtx_packets;
+        if packets == 0 {
+            return None;
+        }
+        Some(self.sum_packet_duration / packets as f64)
+    }
+
+    pub fn get_dtx_ratio(&self) -> f64 {
+        self.dtx_packets as f64 / self.packets as f64
+    }
+
+    pub fn get_fec_ratio(&self) -> f64 {
+        self.fec_packets as f64 / self.packets as f64
+    }
+}
+
+// basic TR 101 290 priority 1 checks of MPEG-TS carried in RTP
+#[derive(Debug, Default)]
+pub struct MpegTsStats {
+    pub pids: BTreeMap<u16, PidStats>,
+    pub sync_byte_errors: usize,
+    pub continuity_errors: usize,
+    // PAT or PMT not repeated within 0.5 s
+    pub pat_errors: usize,
+    pub pmt_errors: usize,
+    pub max_pcr_jitter: Option<f64>, // in seconds
+    last_pat_time: Option<Duration>,
+    // PMT PID to arrival time of its last PMT
+    last_pmt_times: HashMap<u16, Option<Duration>>,
+}
+
+#[derive(Debug, Default)]
+pub struct PidStats {
+    pub packets: usize,
+    pub stream_type: Option<u8>,
+    last_continuity_counter: Option<u8>,
+    // PCR and arrival time
+    last_pcr: Option<(u64, Duration)>,
+}
+
+#[derive(Debug, Default)]
+pub struct TalkspurtStats {
+    pub talkspurts: usize,
+    pub speech_time: Duration,
+    // RTP timestamp increment between consecutive speech packets
+    timestamp_delta: Option<u32>,
+}
+
+// single key press, collapsed from all of the RFC 4733 packets
+// that describe it (including the retransmitted end packets)
+#[derive(Debug)]
+pub struct DtmfEvent {
+    pub event: u8,
+    pub time: Duration,
+    pub duration: u16, // in RTP timestamp units
+    pub clock_rate: Option<u32>,
+    pub volume: u8,
+    pub ended: bool,
+    rtp_timestamp: u32,
+}
+
+impl DtmfEvent {
+    pub fn get_duration(&self) -> Option<Duration> {
+        let clock_rate = self.clock_rate?;
+        Some(Duration::from_secs_f64(
+            self.duration as f64 / clock_rate as f64,
+        ))
+    }
+}
+
+// round-trip time in seconds from a reception report block about the stream
+#[derive(Debug)]
+pub struct RttSample {
+    // arrival time of the report
+    pub time: Duration,
+    // as computed by the sender, see RFC 3550, section 6.4.1,
+    // with the capture host's clock in place of the sender's clock
+    pub sender_rtt: Option<f64>,
+    // between the capture point and the receiver,
+    // if the referenced sender report was captured as well
+    pub capture_rtt: Option<f64>,
+}
+
+// reception quality from a report block compared with the one observed at the capture
+// point since the previous report of the same receiver, loss happening before
+// the capture point is seen by both, while loss after it only by the receiver
+#[derive(Debug)]
+pub struct ReceptionComparison {
+    pub time: Duration,
+    pub reporter_ssrc: u32,
+    pub reported_fraction_lost: f64,
+    pub reported_total_lost: i32,
+    // in seconds, if the clock rate is known
+    pub reported_jitter: Option<f64>,
+    pub observed_fraction_lost: f64,
+    pub observed_total_lost: usize,
+    pub observed_jitter: Option<f64>,
+    // packets expected at the capture point at the time of the report
+    observed_expected: usize,
+}
+
+impl ReceptionComparison {
+    pub fn is_loss_divergent(&self) -> bool {
+        (self.reported_fraction_lost - self.observed_fraction_lost).abs() > MAX_LOSS_DIVERGENCE
+    }
+
+    pub fn is_jitter_divergent(&self) -> bool {
+        match (self.reported_jitter, self.observed_jitter) {
+            (Some(reported), Some(observed)) => (reported - observed).abs() > MAX_JITTER_DIVERGENCE,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RttStats {
+    pub min: f64,
+    pub mean: f64,
+    pub max: f64,
+}
+
+impl RttStats {
+    fn build(rtts: impl Iterator<Item = f64>) -> Option<Self> {
+        let mut stats: Option<Self> = None;
+        let mut count = 0;
+        for rtt in rtts {
+            count += 1;
+            stats = Some(match stats {
+                Some(stats) => Self {
+                    min: stats.min.min(rtt),
+                    mean: stats.mean + (rtt - stats.mean) / count as f64,
+                    max: stats.max.max(rtt),
+                },
+                None => Self {
+                    min: rtt,
+                    mean: rtt,
+                    max: rtt,
+                },
+            });
+        }
+        stats
+    }
+}
+
+// statistics spanning all of the packets are too expensive to build on every frame,
+// so they're built on the first use and dropped whenever the stream changes
+#[derive(Debug, Default)]
+struct StreamCache {
+    burst_stats: OnceCell<BurstStats>,
+    frame_stats: OnceCell<Option<FrameStats>>,
+    quality: OnceCell<Option<QualityScore>>,
+    quality_history: OnceCell<Vec<QualityWindow>>,
 }
 
 #[derive(Debug)]
@@ -53,20 +329,52 @@ pub struct Stream {
     rtp_bytes: usize,
     sum_jitter: f64,
     jitter_count: usize,
-    first_sequence_number: u16,
-    last_sequence_number: u16,
+    sequence_tracker: SequenceTracker,
+    // extended sequence number to the index of the packet in `rtp_packets`
+    received_sequence_numbers: BTreeMap<u32, usize>,
+    pub duplicate_packets: usize,
+    pub reordered_packets: usize,
+    pub late_packets: usize,
+    // packets that consistently follow the previous packet
+    consistent_packets: usize,
     first_time: Duration,
     last_time: Duration,
     sdp: Option<Sdp>,
     pub payload_types: Vec<PayloadType>,
-    // ntp synchronization
-    pub ntp_rtp: Option<(u64, u32)>,
+    pub layers: BTreeMap<Layer, LayerStats>,
+    pub opus_stats: Option<OpusStats>,
+    pub mpeg_ts_stats: Option<MpegTsStats>,
+    pub dtmf_events: Vec<DtmfEvent>,
+    pub talkspurt_stats: Option<TalkspurtStats>,
+    pub is_rtx: bool,
+    // identification in BUNDLE and simulcast, from the latest packet carrying them
+    pub mid: Option<String>,
+    pub rid: Option<String>,
+    pub repaired_rid: Option<String>,
+    // ntp synchronization, from the latest valid sender report,
+    // together with its arrival time
+    pub ntp_rtp: Option<(u64, u32, Duration)>,
     pub estimated_clock_rate: Option<f64>,
+    // clock rate inferred from RTP timestamps and arrival times,
+    // used if the payload type doesn't define it (dynamic payload type without SDP)
+    pub inferred_clock_rate: Option<u32>,
+    // the first valid sender report and RTP timestamp progress since then,
+    // used to estimate the clock rate
+    first_ntp_rtp: Option<(u64, u32)>,
+    rtp_time_progress: u64,
+    valid_sender_reports: usize,
+    pub rtt_samples: Vec<RttSample>,
+    pub reception_comparisons: Vec<ReceptionComparison>,
+    pub events: Vec<QualityEvent>,
+    rule_states: Vec<RuleState>,
+    cache: StreamCache,
 }
 
 impl Stream {
-    pub fn new(packet: &Packet, rtp: &RtpPacket, default_alias: String) -> Self {
-        let rtp_info = RtpInfo {
+    pub fn new(packet: &Packet, rtp: &RtpPacket, default_alias: String, sdp: Option<Sdp>) -> Self {
+        let payload_type = resolve_payload_type(&sdp, rtp);
+        let sequence_tracker = SequenceTracker::new(rtp.sequence_number);
+        let mut rtp_info = RtpInfo {
             packet: rtp.clone(),
             id: packet.id,
             time: packet.timestamp,
@@ -74,11 +382,31 @@ impl Stream {
             time_delta: Duration::from_secs(0),
             jitter: Some(0.0),
             prev_lost: false,
+            extended_sequence_number: sequence_tracker.get_extended_max(),
             bytes: packet.length as usize,
             bitrate: packet.length as usize * 8,
             packet_rate: 1,
+            payload_info: get_payload_info(&sdp, &payload_type, packet, rtp),
+            dependency_descriptor: get_dependency_descriptor(&sdp, rtp),
+            talkspurt: None,
+            talkspurt_start: false,
         };
 
+        let mut talkspurt_stats = None;
+        update_talkspurts(&mut talkspurt_stats, &[], &payload_type, &mut rtp_info);
+        let mut layers = BTreeMap::new();
+        update_layers(&mut layers, &rtp_info);
+        let mut opus_stats = None;
+        update_opus_stats(&mut opus_stats, &rtp_info);
+        let mut mpeg_ts_stats = None;
+        update_mpeg_ts_stats(&mut mpeg_ts_stats, &rtp_info);
+        let mut dtmf_events = Vec::new();
+        update_dtmf_events(&mut dtmf_events, &payload_type, &rtp_info);
+        let is_rtx = is_rtx_payload_type(&payload_type);
+        let mid = get_sdes_extension(&sdp, rtp, MID_URI);
+        let rid = get_sdes_extension(&sdp, rtp, RID_URI);
+        let repaired_rid = get_sdes_extension(&sdp, rtp, REPAIRED_RID_URI);
+
         Self {
             source_addr: packet.source_addr,
             destination_addr: packet.destination_addr,
@@ -93,20 +421,90 @@ impl Stream {
             sum_jitter: 0.0,
             jitter_count: 0,
             cname: None,
-            first_sequence_number: rtp.sequence_number,
-            last_sequence_number: rtp.sequence_number,
+            received_sequence_numbers: BTreeMap::from([(sequence_tracker.get_extended_max(), 0)]),
+            sequence_tracker,
+            duplicate_packets: 0,
+            reordered_packets: 0,
+            late_packets: 0,
+            consistent_packets: 0,
             first_time: packet.timestamp,
             last_time: packet.timestamp,
-            sdp: None,
-            payload_types: Vec::new(),
+            sdp,
+            payload_types: vec![payload_type],
+            layers,
+            opus_stats,
+            mpeg_ts_stats,
+            dtmf_events,
+            talkspurt_stats,
+            is_rtx,
+            mid,
+            rid,
+            repaired_rid,
             ntp_rtp: None,
             estimated_clock_rate: None,
+            inferred_clock_rate: None,
+            first_ntp_rtp: None,
+            rtp_time_progress: 0,
+            valid_sender_reports: 0,
+            rtt_samples: Vec::new(),
+            reception_comparisons: Vec::new(),
+            events: Vec::new(),
+            rule_states: Vec::new(),
+            cache: StreamCache::default(),
         }
     }
 
-    pub fn add_sdp(&mut self, sdp: Sdp) {
-        self.sdp = Some(sdp);
-        self.recalculate();
+    pub fn get_rtp_info(&self, id: usize) -> Option<&RtpInfo> {
+        // packets are always added in the order of their ids
+        let ix = self
+            .rtp_packets
+            .binary_search_by_key(&id, |rtp_info| rtp_info.id)
+            .ok()?;
+        self.rtp_packets.get(ix)
+    }
+
+    // role of the stream in its group, e.g. a simulcast layer or a repair stream
+    pub fn get_role(&self) -> Option<String> {
+        if let Some(repaired_rid) = &self.repaired_rid {
+            return Some(format!("repairs {}", repaired_rid));
+        }
+        if self.is_rtx {
+            return Some("RTX".to_string());
+        }
+        self.rid.as_ref().map(|rid| format!("rid {}", rid))
+    }
+
+    pub fn get_sdp(&self) -> Option<&Sdp> {
+        self.sdp.as_ref()
+    }
+
+    pub fn get_first_time(&self) -> Duration {
+        self.first_time
+    }
+
+    pub fn get_lost_sequence_numbers(&self) -> HashSet<u32> {
+        let (Some((first, _)), Some((last, _))) = (
+            self.received_sequence_numbers.first_key_value(),
+            self.received_sequence_numbers.last_key_value(),
+        ) else {
+            return HashSet::new();
+        };
+
+        (*first..=*last)
+            .filter(|extended| !self.received_sequence_numbers.contains_key(extended))
+            .collect()
+    }
+
+    // sequence numbers of the packets retransmitted in this (RTX) stream,
+    // with the ids of the packets carrying them
+    pub fn get_original_sequence_numbers(&self) -> Vec<(usize, u16)> {
+        self.rtp_packets
+            .iter()
+            .filter_map(|rtp| match rtp.payload_info {
+                Some(PayloadInfo::Rtx(ref rtx)) => Some((rtp.id, rtx.original_sequence_number)),
+                _ => None,
+            })
+            .collect()
     }
 
     pub fn get_duration(&self) -> Duration {
@@ -114,7 +512,22 @@ impl Stream {
     }
 
     pub fn get_expected_count(&self) -> usize {
-        (self.last_sequence_number + 1 - self.first_sequence_number) as usize
+        let (Some((first, _)), Some((last, _))) = (
+            self.received_sequence_numbers.first_key_value(),
+            self.received_sequence_numbers.last_key_value(),
+        ) else {
+            return 0;
+        };
+        (last - first) as usize + 1
+    }
+
+    pub fn get_received_sequence_numbers(&self) -> &BTreeMap<u32, usize> {
+        &self.received_sequence_numbers
+    }
+
+    // duplicates are not counted as received
+    pub fn get_lost_count(&self) -> usize {
+        self.get_expected_count() - self.received_sequence_numbers.len()
     }
 
     pub fn get_mean_jitter(&self) -> Option<f64> {
@@ -139,7 +552,104 @@ impl Stream {
         self.rtp_packets.len() as f64 / duration
     }
 
+    pub fn get_layer_bitrate(&self, stats: &LayerStats) -> f64 {
+        let duration = self.get_duration().as_secs_f64();
+        stats.bytes as f64 * 8.0 / duration
+    }
+
+    pub fn get_layer_frame_rate(&self, stats: &LayerStats) -> f64 {
+        let duration = self.get_duration().as_secs_f64();
+        stats.frames as f64 / duration
+    }
+
+    // low for packets wrongly parsed as RTP (e.g. with `parse_as`),
+    // as their sequence numbers and timestamps are random
+    pub fn get_rtp_confidence(&self) -> f64 {
+        if self.rtp_packets.len() < 2 {
+            return 0.0;
+        }
+        self.consistent_packets as f64 / (self.rtp_packets.len() - 1) as f64
+    }
+
+    pub fn get_pid_bitrate(&self, stats: &PidStats) -> f64 {
+        let duration = self.get_duration().as_secs_f64();
+        (stats.packets * TS_PACKET_LENGTH) as f64 * 8.0 / duration
+    }
+
+    // payload type of the latest media packet
+    pub fn get_media_payload_type(&self) -> Option<PayloadType> {
+        let rtp_info = self
+            .rtp_packets
+            .iter()
+            .rev()
+            .find(|rtp_info| rtp_info.follows_media_clock())?;
+        Some(resolve_payload_type(&self.sdp, &rtp_info.packet))
+    }
+
+    pub fn get_media_type(&self) -> Option<MediaType> {
+        Some(self.get_media_payload_type()?.media_type)
+    }
+
+    // header extension id negotiated in the SDP
+    pub fn get_extension_id(&self, uri: &str) -> Option<u8> {
+        self.sdp.as_ref()?.get_extension_id(uri)
+    }
+
+    pub fn get_nominal_clock_rate(&self) -> Option<u32> {
+        self.get_clock_rate(&self.get_media_payload_type()?)
+    }
+
+    pub fn is_clock_rate_inferred(&self) -> bool {
+        let payload_type = self.get_media_payload_type();
+        payload_type.is_some_and(|payload_type| payload_type.clock_rate.is_none())
+            && self.inferred_clock_rate.is_some()
+    }
+
+    fn get_clock_rate(&self, payload_type: &PayloadType) -> Option<u32> {
+        payload_type.clock_rate.or(self.inferred_clock_rate)
+    }
+
+    // in ppm, positive if the sender's media clock runs faster than its wall clock
+    pub fn get_clock_drift(&self) -> Option<f64> {
+        let estimated_clock_rate = self.estimated_clock_rate?;
+        let clock_rate = self.get_nominal_clock_rate()?;
+        Some((estimated_clock_rate / clock_rate as f64 - 1.0) * 1e6)
+    }
+
+    pub fn get_burst_stats(&self) -> &BurstStats {
+        self.cache
+            .burst_stats
+            .get_or_init(|| BurstStats::build(self))
+    }
+
+    pub fn get_frame_stats(&self) -> Option<&FrameStats> {
+        self.cache
+            .frame_stats
+            .get_or_init(|| FrameStats::build(self))
+            .as_ref()
+    }
+
+    pub fn get_quality(&self) -> Option<QualityScore> {
+        *self
+            .cache
+            .quality
+            .get_or_init(|| quality::get_stream_quality(self))
+    }
+
+    pub fn get_quality_history(&self) -> &[QualityWindow] {
+        self.cache
+            .quality_history
+            .get_or_init(|| quality::get_quality_history(self))
+    }
+
+    pub fn get_speech_activity(&self, stats: &TalkspurtStats) -> f64 {
+        let duration = self.get_duration().as_secs_f64();
+        stats.speech_time.as_secs_f64() / duration
+    }
+
     pub fn add_rtp_packet(&mut self, packet: &Packet, rtp: &RtpPacket) {
+        self.cache = StreamCache::default();
+        let payload_type = resolve_payload_type(&self.sdp, rtp);
         let rtp_info = RtpInfo {
             packet: rtp.clone(),
             id: packet.id,
@@ -148,32 +658,59 @@ impl Stream {
             time_delta: Duration::from_secs(0),
             jitter: None,
             prev_lost: true,
+            extended_sequence_number: 0,
             bytes: packet.length as usize,
             bitrate: 0,
             packet_rate: 0,
+            payload_info: get_payload_info(&self.sdp, &payload_type, packet, rtp),
+            dependency_descriptor: get_dependency_descriptor(&self.sdp, rtp),
+            talkspurt: None,
+            talkspurt_start: false,
         };
 
         self.update_rtp_parameters(rtp_info);
     }
 
+    // evaluates the rules after the latest packet
+    pub fn check_quality_rules(&mut self, rules: &[QualityRule]) {
+        // gaps and losses of repair streams are expected
+        if self.is_rtx || self.repaired_rid.is_some() {
+            return;
+        }
+
+        let mut states = std::mem::take(&mut self.rule_states);
+        states.resize_with(rules.len(), RuleState::default);
+        for (rule, state) in rules.iter().zip(states.iter_mut()) {
+            if let Some(event) = events::evaluate(self, rule, state) {
+                self.events.push(event);
+            }
+        }
+        self.rule_states = states;
+    }
+
+    // evaluates the rules that can fire without a new packet of this stream,
+    // `time` is the time of the capture's latest packet
+    pub fn check_idle_quality_rules(&mut self, rules: &[QualityRule], time: Duration) {
+        if self.is_rtx || self.repaired_rid.is_some() {
+            return;
+        }
+
+        let mut states = std::mem::take(&mut self.rule_states);
+        states.resize_with(rules.len(), RuleState::default);
+        for (rule, state) in rules.iter().zip(states.iter_mut()) {
+            if let Some(event) = events::evaluate_idle(self, rule, state, time) {
+                self.events.push(event);
+            }
+        }
+        self.rule_states = states;
+    }
+
     pub fn add_rtcp_packet(&mut self, id: usize, timestamp: Duration, packet: &RtcpPacket) {
+        self.cache = StreamCache::default();
         match &packet {
             RtcpPacket::SourceDescription(sd) => self.update_sdes_items(sd),
             RtcpPacket::ReceiverReport(_rr) => {}
-            RtcpPacket::SenderReport(sr) => {
-                // let mut revisit_packets = false;
-                if let Some((ntp_time, _rtp_time)) = self.ntp_rtp {
-                    // revisit_packets = self.estimated_clock_rate.is_none();
-                    // let rtp_diff = sr.rtp_time - rtp_time;
-                    let _secs_diff = ntp_to_f64(sr.ntp_time) - ntp_to_f64(ntp_time);
-                    // self.estimated_clock_rate = Some(rtp_diff as f64 / secs_diff);
-                } else {
-                    // revisit_packets = true;
-                }
-                // self.ntp_rtp = Some((sr.ntp_time, sr.rtp_time));
-                // TODO: use the estiamted clock rate to set ntp time in rtp_info
-                // TODO: sometimes ntp timestamps are bs
-            }
+            RtcpPacket::SenderReport(sr) => self.update_ntp_mapping(sr, timestamp),
             _ => {}
         }
 
@@ -181,76 +718,315 @@ impl Stream {
         self.rtcp_packets.push(rtcp_info);
     }
 
-    fn recalculate(&mut self) {
-        let mut rtp_packets = std::mem::take(&mut self.rtp_packets).into_iter();
-        let rtp_info = rtp_packets.next().unwrap();
-        self.bytes = rtp_info.bytes;
-        self.rtp_bytes = rtp_info.packet.payload_length;
-        self.max_jitter = 0.0;
-        self.sum_jitter = 0.0;
-        self.jitter_count = 0;
-        self.first_sequence_number = rtp_info.packet.sequence_number;
-        self.last_sequence_number = rtp_info.packet.sequence_number;
-        self.first_time = rtp_info.time;
-        self.last_time = rtp_info.time;
-        self.rtp_packets = vec![rtp_info];
-
-        rtp_packets.for_each(|rtp| self.update_rtp_parameters(rtp));
-    }
-
     fn update_rtp_parameters(&mut self, mut rtp_info: RtpInfo) {
-        rtp_info.time_delta = rtp_info.time - self.rtp_packets.last().unwrap().time;
+        let last = self.rtp_packets.last().unwrap();
+        rtp_info.time_delta = rtp_info.time - last.time;
+        if is_consistent(
+            (last.packet.sequence_number, last.packet.timestamp),
+            (rtp_info.packet.sequence_number, rtp_info.packet.timestamp),
+        ) {
+            self.consistent_packets += 1;
+        }
 
-        self.estimate_ntp_time(&mut rtp_info);
+        rtp_info.ntp_time = self.get_sender_ntp_time(&rtp_info);
+        let payload_type = resolve_payload_type(&self.sdp, &rtp_info.packet);
+        if payload_type.clock_rate.is_none() {
+            self.update_inferred_clock_rate(&rtp_info);
+        }
+        update_talkspurts(
+            &mut self.talkspurt_stats,
+            &self.rtp_packets,
+            &payload_type,
+            &mut rtp_info,
+        );
         self.update_jitter(&mut rtp_info);
         self.update_rates(&mut rtp_info);
 
         self.bytes += rtp_info.bytes;
         self.rtp_bytes += rtp_info.packet.payload_length;
+        update_layers(&mut self.layers, &rtp_info);
+        update_opus_stats(&mut self.opus_stats, &rtp_info);
+        update_mpeg_ts_stats(&mut self.mpeg_ts_stats, &rtp_info);
+        update_dtmf_events(&mut self.dtmf_events, &payload_type, &rtp_info);
+        self.is_rtx |= is_rtx_payload_type(&payload_type);
+        self.update_stream_ids(&rtp_info);
 
         self.first_time = min(self.first_time, rtp_info.time);
         self.last_time = max(self.last_time, rtp_info.time);
-        self.first_sequence_number =
-            min(self.first_sequence_number, rtp_info.packet.sequence_number);
-        self.last_sequence_number = max(self.last_sequence_number, rtp_info.packet.sequence_number);
-
-        self.update_prev_lost(&mut rtp_info);
+        self.update_sequence_numbers(&mut rtp_info);
         self.rtp_packets.push(rtp_info);
     }
 
-    fn estimate_ntp_time(&self, _rtp_info: &mut RtpInfo) {
-        // TODO
+    // reception report about this stream, sent by its receiver
+    pub fn add_reception_report(
+        &mut self,
+        time: Duration,
+        reporter_ssrc: u32,
+        report: &ReceptionReport,
+    ) {
+        self.cache = StreamCache::default();
+        self.update_reception_comparisons(time, reporter_ssrc, report);
+
+        // receiver hasn't got any sender report yet
+        if report.last_sender_report == 0 {
+            return;
+        }
+
+        let delay = report.delay as f64 / DLSR_UNITS_PER_SECOND;
+        let arrival = (time_to_ntp(time) >> 16) as u32;
+        let sender_rtt = arrival
+            .wrapping_sub(report.last_sender_report)
+            .wrapping_sub(report.delay) as i32 as f64
+            / DLSR_UNITS_PER_SECOND;
+        let sender_rtt = Some(sender_rtt).filter(|rtt| (0.0..=MAX_SENDER_RTT).contains(rtt));
+
+        let capture_rtt = self
+            .rtcp_packets
+            .iter()
+            .rev()
+            .find_map(|rtcp_info| match &rtcp_info.packet {
+                RtcpPacket::SenderReport(sr)
+                    if (sr.ntp_time >> 16) as u32 == report.last_sender_report =>
+                {
+                    Some(rtcp_info.time)
+                }
+                _ => None,
+            })
+            .map(|sr_time| time.saturating_sub(sr_time).as_secs_f64() - delay)
+            .filter(|rtt| *rtt >= 0.0);
+
+        if sender_rtt.is_none() && capture_rtt.is_none() {
+            return;
+        }
+
+        self.rtt_samples.push(RttSample {
+            time,
+            sender_rtt,
+            capture_rtt,
+        });
     }
 
-    fn get_packet_payload_type(&mut self, rtp_info: &RtpInfo) -> PayloadType {
-        let id = &rtp_info.packet.payload_type.id;
-        if let Some(last_payload_type) = self.payload_types.last() {
-            if last_payload_type.id != *id {
-                self.payload_types
-                    .push(rtp_info.packet.payload_type.clone())
+    fn update_reception_comparisons(
+        &mut self,
+        time: Duration,
+        reporter_ssrc: u32,
+        report: &ReceptionReport,
+    ) {
+        let (previous_expected, previous_lost) = self
+            .reception_comparisons
+            .iter()
+            .rev()
+            .find(|comparison| comparison.reporter_ssrc == reporter_ssrc)
+            .map_or((0, 0), |comparison| {
+                (comparison.observed_expected, comparison.observed_total_lost)
+            });
+        let expected = self.get_expected_count();
+        let lost = self.get_lost_count();
+        let interval_expected = expected.saturating_sub(previous_expected);
+        let observed_fraction_lost = if interval_expected == 0 {
+            0.0
+        } else {
+            lost.saturating_sub(previous_lost) as f64 / interval_expected as f64
+        };
+
+        let reported_jitter = self
+            .get_nominal_clock_rate()
+            .map(|clock_rate| report.jitter as f64 / clock_rate as f64);
+        let observed_jitter = self
+            .rtp_packets
+            .iter()
+            .rev()
+            .find_map(|rtp_info| rtp_info.jitter);
+
+        self.reception_comparisons.push(ReceptionComparison {
+            time,
+            reporter_ssrc,
+            // fixed point number with the binary point at the left edge
+            reported_fraction_lost: report.fraction_lost as f64 / 256.0,
+            // 24-bit signed integer
+            reported_total_lost: ((report.total_lost << 8) as i32) >> 8,
+            reported_jitter,
+            observed_fraction_lost,
+            observed_total_lost: lost,
+            observed_jitter,
+            observed_expected: expected,
+        });
+    }
+
+    pub fn get_sender_rtt_stats(&self) -> Option<RttStats> {
+        RttStats::build(
+            self.rtt_samples
+                .iter()
+                .filter_map(|sample| sample.sender_rtt),
+        )
+    }
+
+    pub fn get_capture_rtt_stats(&self) -> Option<RttStats> {
+        RttStats::build(
+            self.rtt_samples
+                .iter()
+                .filter_map(|sample| sample.capture_rtt),
+        )
+    }
+
+    fn update_ntp_mapping(&mut self, sr: &SenderReport, time: Duration) {
+        // senders without a synchronized clock often send zero NTP time
+        if sr.ntp_time == 0 {
+            return;
+        }
+
+        // rejected reports are not taken into account when verifying the next ones
+        let last_ntp_rtp = self.ntp_rtp;
+        let is_valid =
+            last_ntp_rtp.is_some_and(|last| self.is_sender_report_consistent(last, sr, time));
+
+        match last_ntp_rtp {
+            Some((_, rtp_time, _)) if is_valid => {
+                self.rtp_time_progress += sr.rtp_time.wrapping_sub(rtp_time) as u64;
+                self.valid_sender_reports += 1;
+                self.ntp_rtp = Some((sr.ntp_time, sr.rtp_time, time));
+
+                let (first_ntp_time, _) = self.first_ntp_rtp.unwrap();
+                let interval = ntp_to_f64(sr.ntp_time) - ntp_to_f64(first_ntp_time);
+                if interval >= MIN_CLOCK_RATE_INTERVAL {
+                    self.estimated_clock_rate = Some(self.rtp_time_progress as f64 / interval);
+                }
             }
+            // a single report cannot be verified, so if it doesn't match
+            // the next one, the mapping starts over from the newer report
+            _ if self.valid_sender_reports <= 1 => {
+                self.ntp_rtp = Some((sr.ntp_time, sr.rtp_time, time));
+                self.first_ntp_rtp = Some((sr.ntp_time, sr.rtp_time));
+                self.rtp_time_progress = 0;
+                self.valid_sender_reports = 1;
+
+                // packets received before are mapped retroactively
+                let ntp_times: Vec<_> = self
+                    .rtp_packets
+                    .iter()
+                    .map(|rtp_info| self.get_sender_ntp_time(rtp_info))
+                    .collect();
+                self.rtp_packets
+                    .iter_mut()
+                    .zip(ntp_times)
+                    .for_each(|(rtp_info, ntp_time)| rtp_info.ntp_time = ntp_time);
+            }
+            _ => {}
+        }
+    }
+
+    fn is_sender_report_consistent(
+        &self,
+        (ntp_time, rtp_time, last_time): (u64, u32, Duration),
+        sr: &SenderReport,
+        time: Duration,
+    ) -> bool {
+        let ntp_diff = ntp_to_f64(sr.ntp_time) - ntp_to_f64(ntp_time);
+        let arrival_diff = time.saturating_sub(last_time);
+        if ntp_diff <= 0.0 || (ntp_diff - arrival_diff.as_secs_f64()).abs() > MAX_NTP_DEVIATION {
+            return false;
+        }
+
+        let rtp_diff = sr.rtp_time.wrapping_sub(rtp_time) as i32;
+        if rtp_diff < 0 {
+            return false;
+        }
+
+        let Some(clock_rate) = self.get_nominal_clock_rate() else {
+            return true;
+        };
+        let deviation = rtp_diff as f64 / ntp_diff / clock_rate as f64 - 1.0;
+        deviation.abs() <= MAX_CLOCK_RATE_DEVIATION
+    }
+
+    fn get_sender_ntp_time(&self, rtp_info: &RtpInfo) -> Option<u64> {
+        let (ntp_time, rtp_time, _) = self.ntp_rtp?;
+        let clock_rate = match self.estimated_clock_rate {
+            Some(clock_rate) => clock_rate,
+            None => {
+                let payload_type = resolve_payload_type(&self.sdp, &rtp_info.packet);
+                self.get_clock_rate(&payload_type)? as f64
+            }
+        };
+
+        let rtp_diff = rtp_info.packet.timestamp.wrapping_sub(rtp_time) as i32;
+        let ntp_diff = rtp_diff as f64 / clock_rate * NTP_UNITS_PER_SECOND;
+        Some(ntp_time.wrapping_add_signed(ntp_diff as i64))
+    }
+
+    fn get_packet_payload_type(&mut self, rtp_info: &RtpInfo) -> PayloadType {
+        let id = rtp_info.packet.payload_type.id;
+        // FEC packets are interleaved with the media packets,
+        // so their payload type is not considered a change of the stream's payload type
+        let is_change = if rtp_info.is_fec() {
+            !self.payload_types.iter().any(|pt| pt.id == id)
         } else {
+            let last_id = self
+                .rtp_packets
+                .iter()
+                .rev()
+                .find(|rtp| !rtp.is_fec())
+                .map(|rtp| rtp.packet.payload_type.id);
+            last_id != Some(id)
+        };
+        if is_change {
             self.payload_types
                 .push(rtp_info.packet.payload_type.clone())
         }
 
-        if let Some(sdp) = &self.sdp {
-            if let Some(pt) = sdp.payload_types.get(id) {
-                return pt.clone();
-            }
+        resolve_payload_type(&self.sdp, &rtp_info.packet)
+    }
+
+    fn update_inferred_clock_rate(&mut self, rtp_info: &RtpInfo) {
+        if !rtp_info.follows_media_clock() {
+            return;
+        }
+        let Some(first) = self
+            .rtp_packets
+            .iter()
+            .find(|rtp_info| rtp_info.follows_media_clock())
+        else {
+            return;
         };
 
-        rtp_info.packet.payload_type.clone()
+        let interval = rtp_info.time.saturating_sub(first.time);
+        if !(MIN_CLOCK_RATE_INFERENCE_TIME..=MAX_CLOCK_RATE_INFERENCE_TIME).contains(&interval) {
+            return;
+        }
+        let timestamp_diff = rtp_info
+            .packet
+            .timestamp
+            .wrapping_sub(first.packet.timestamp) as i32;
+        let clock_rate = timestamp_diff as f64 / interval.as_secs_f64();
+        if let Some(clock_rate) = snap_clock_rate(clock_rate) {
+            self.inferred_clock_rate = Some(clock_rate);
+        }
     }
 
     fn update_jitter(&mut self, rtp_info: &mut RtpInfo) {
         let payload_type = self.get_packet_payload_type(rtp_info);
 
-        let Some(clock_rate) = payload_type.clock_rate else {
+        let Some(clock_rate) = self.get_clock_rate(&payload_type) else {
             return;
         };
 
-        let prev_rtp_info = self.rtp_packets.last().unwrap();
+        // telephone events share the SSRC with the audio, but are sent in bursts
+        // with timestamp of the beginning of the event, which would distort the jitter,
+        // comfort noise packets are sent sporadically during silence
+        // and FEC packets are sent in bursts after the media they protect
+        if !rtp_info.follows_media_clock() {
+            return;
+        }
+
+        let Some(prev_rtp_info) = self
+            .rtp_packets
+            .iter()
+            .rev()
+            .find(|rtp| rtp.follows_media_clock())
+        else {
+            rtp_info.jitter = Some(0.0);
+            return;
+        };
 
         let is_new = rtp_info.packet.payload_type.id != prev_rtp_info.packet.payload_type.id;
         if is_new {
@@ -258,13 +1034,24 @@ impl Stream {
             return;
         }
 
+        // the silence period between talkspurts is not a transit time variation,
+        // so the estimate starts over with the new talkspurt
+        if rtp_info.talkspurt_start {
+            rtp_info.jitter = Some(0.0);
+            return;
+        }
+
         let unit = 1.0 / clock_rate as f64;
         let arrival_diff = (rtp_info.time - prev_rtp_info.time).as_secs_f64();
         let rtp_timestamp_diff =
             (rtp_info.packet.timestamp as i64 - prev_rtp_info.packet.timestamp as i64) as f64;
         let diff = arrival_diff - rtp_timestamp_diff * unit;
 
-        let prev_jitter = prev_rtp_info.jitter.unwrap();
+        // previous packets might have no jitter until the clock rate is inferred
+        let Some(prev_jitter) = prev_rtp_info.jitter else {
+            rtp_info.jitter = Some(0.0);
+            return;
+        };
         let jitter = prev_jitter + (diff.abs() - prev_jitter) / 16.0;
 
         rtp_info.jitter = Some(jitter);
@@ -276,6 +1063,20 @@ impl Stream {
         self.jitter_count += 1;
     }
 
+    fn update_stream_ids(&mut self, rtp_info: &RtpInfo) {
+        // the extensions are usually sent only until the receiver learns the SSRC
+        let rtp = &rtp_info.packet;
+        if let Some(mid) = get_sdes_extension(&self.sdp, rtp, MID_URI) {
+            self.mid = Some(mid);
+        }
+        if let Some(rid) = get_sdes_extension(&self.sdp, rtp, RID_URI) {
+            self.rid = Some(rid);
+        }
+        if let Some(repaired_rid) = get_sdes_extension(&self.sdp, rtp, REPAIRED_RID_URI) {
+            self.repaired_rid = Some(repaired_rid);
+        }
+    }
+
     fn update_rates(&self, rtp_info: &mut RtpInfo) {
         let cutoff = rtp_info.time.checked_sub(Duration::from_secs(1)).unwrap();
 
@@ -292,26 +1093,49 @@ impl Stream {
         rtp_info.bitrate = bytes * 8;
     }
 
-    fn update_prev_lost(&mut self, rtp_info: &mut RtpInfo) {
-        if rtp_info.packet.sequence_number == self.first_sequence_number {
-            rtp_info.prev_lost = false;
-            return;
-        }
+    fn update_sequence_numbers(&mut self, rtp_info: &mut RtpInfo) {
+        let (extended, update) = self
+            .sequence_tracker
+            .update(rtp_info.packet.sequence_number);
+        rtp_info.extended_sequence_number = extended;
+        rtp_info.prev_lost = false;
 
-        self.rtp_packets
-            .iter_mut()
-            .rev()
-            // FIXME: we only check last 10 packets, may lead to bugs
-            .take(10)
-            .for_each(|pack| {
-                if pack.packet.sequence_number + 1 == rtp_info.packet.sequence_number {
-                    rtp_info.prev_lost = false;
+        match update {
+            SequenceUpdate::InOrder => {}
+            SequenceUpdate::Jump => return,
+            SequenceUpdate::Restart => self.received_sequence_numbers.clear(),
+            SequenceUpdate::OutOfOrder => {
+                if self.received_sequence_numbers.contains_key(&extended) {
+                    self.duplicate_packets += 1;
+                    return;
                 }
 
-                if pack.packet.sequence_number == rtp_info.packet.sequence_number + 1 {
-                    pack.prev_lost = false;
+                // delay after the arrival of the first packet that should have followed it
+                let first_following_time = self
+                    .received_sequence_numbers
+                    .range(extended + 1..)
+                    .map(|(_, ix)| self.rtp_packets[*ix].time)
+                    .min()
+                    .unwrap_or(rtp_info.time);
+                if rtp_info.time.saturating_sub(first_following_time) > LATE_PACKET_DELAY {
+                    self.late_packets += 1;
+                } else {
+                    self.reordered_packets += 1;
                 }
-            });
+            }
+        }
+
+        let is_first = self
+            .received_sequence_numbers
+            .first_key_value()
+            .is_none_or(|(first, _)| *first >= extended);
+        rtp_info.prev_lost =
+            !is_first && !self.received_sequence_numbers.contains_key(&(extended - 1));
+        if let Some(ix) = self.received_sequence_numbers.get(&(extended + 1)) {
+            self.rtp_packets[*ix].prev_lost = false;
+        }
+        self.received_sequence_numbers
+            .insert(extended, self.rtp_packets.len());
     }
 
     fn update_sdes_items(&mut self, source_description: &SourceDescription) {
@@ -333,3 +1157,318 @@ impl Stream {
         }
     }
 }
+
+fn update_layers(layers: &mut BTreeMap<Layer, LayerStats>, rtp_info: &RtpInfo) {
+    let Some(layer) = rtp_info
+        .payload_info
+        .as_ref()
+        .and_then(|payload_info| payload_info.get_layer())
+    else {
+        return;
+    };
+
+    let timestamp = rtp_info.packet.timestamp;
+    let stats = layers.entry(layer).or_insert(LayerStats {
+        bytes: 0,
+        frames: 1,
+        last_timestamp: timestamp,
+    });
+
+    // packets of a single frame share the RTP timestamp
+    if stats.last_timestamp != timestamp {
+        stats.frames += 1;
+        stats.last_timestamp = timestamp;
+    }
+    stats.bytes += rtp_info.bytes;
+}
+
+fn update_opus_stats(opus_stats: &mut Option<OpusStats>, rtp_info: &RtpInfo) {
+    let Some(PayloadInfo::Opus(opus)) = rtp_info.get_primary_payload_info() else {
+        return;
+    };
+
+    let stats = opus_stats.get_or_insert_with(OpusStats::default);
+    stats.packets += 1;
+    if opus.is_dtx {
+        stats.dtx_packets += 1;
+    } else {
+        stats.sum_packet_duration += opus.get_packet_duration();
+    }
+    if opus.has_fec {
+        stats.fec_packets += 1;
+    }
+}
+
+fn update_mpeg_ts_stats(mpeg_ts_stats: &mut Option<MpegTsStats>, rtp_info: &RtpInfo) {
+    let Some(PayloadInfo::MpegTs(ts)) = &rtp_info.payload_info else {
+        return;
+    };
+
+    let stats = mpeg_ts_stats.get_or_insert_with(MpegTsStats::default);
+    let time = rtp_info.time;
+    for packet in &ts.packets {
+        if !packet.is_sync_byte_valid {
+            stats.sync_byte_errors += 1;
+            continue;
+        }
+        if packet.pid == NULL_PID {
+            continue;
+        }
+
+        let pid_stats = stats.pids.entry(packet.pid).or_default();
+        pid_stats.packets += 1;
+
+        // counter increments only with payload, single duplicate packet is allowed
+        if let Some(last) = pid_stats.last_continuity_counter {
+            let expected = if packet.has_payload {
+                (last + 1) & 0x0f
+            } else {
+                last
+            };
+            let is_duplicate = packet.has_payload && packet.continuity_counter == last;
+            if packet.continuity_counter != expected && !is_duplicate && !packet.discontinuity {
+                stats.continuity_errors += 1;
+            }
+        }
+        pid_stats.last_continuity_counter = Some(packet.continuity_counter);
+
+        // difference between PCR progression and arrival time progression
+        if let Some(pcr) = packet.pcr {
+            if let Some((last_pcr, last_time)) =
+                pid_stats.last_pcr.filter(|_| !packet.discontinuity)
+            {
+                // PCR wraps around roughly every 26.5 hours
+                let pcr_delta = (pcr + PCR_MODULUS - last_pcr) % PCR_MODULUS;
+                let pcr_delta = pcr_delta as f64 / PCR_CLOCK_RATE;
+                let time_delta = time.as_secs_f64() - last_time.as_secs_f64();
+                let jitter = (pcr_delta - time_delta).abs();
+                stats.max_pcr_jitter =
+                    Some(stats.max_pcr_jitter.map_or(jitter, |max| max.max(jitter)));
+            }
+            pid_stats.last_pcr = Some((pcr, time));
+        }
+    }
+
+    if let Some(programs) = &ts.programs {
+        if is_repetition_late(stats.last_pat_time, time) {
+            stats.pat_errors += 1;
+        }
+        stats.last_pat_time = Some(time);
+        for program in programs {
+            stats.last_pmt_times.entry(program.pmt_pid).or_insert(None);
+        }
+    }
+
+    for pmt in &ts.pmts {
+        for (stream_type, pid) in &pmt.streams {
+            stats.pids.entry(*pid).or_default().stream_type = Some(*stream_type);
+        }
+        // only PMTs referenced by PAT are checked
+        let Some(last_pmt_time) = stats.last_pmt_times.get_mut(&pmt.pid) else {
+            continue;
+        };
+        if is_repetition_late(*last_pmt_time, time) {
+            stats.pmt_errors += 1;
+        }
+        *last_pmt_time = Some(time);
+    }
+}
+
+fn is_repetition_late(last_time: Option<Duration>, time: Duration) -> bool {
+    last_time.is_some_and(|last_time| time.saturating_sub(last_time) > MAX_PSI_INTERVAL)
+}
+
+// new talkspurt starts with a marker bit, after comfort noise
+// or with a gap in RTP timestamps without a gap in sequence numbers
+fn update_talkspurts(
+    talkspurt_stats: &mut Option<TalkspurtStats>,
+    rtp_packets: &[RtpInfo],
+    payload_type: &PayloadType,
+    rtp_info: &mut RtpInfo,
+) {
+    let is_audio = matches!(payload_type.media_type, MediaType::Audio);
+    if !is_audio || rtp_info.is_telephone_event() || rtp_info.is_fec() {
+        return;
+    }
+
+    let stats = talkspurt_stats.get_or_insert_with(TalkspurtStats::default);
+    if rtp_info.is_comfort_noise() {
+        return;
+    }
+
+    let after_comfort_noise = rtp_packets
+        .iter()
+        .rev()
+        .find(|rtp| !rtp.is_telephone_event())
+        .is_some_and(|rtp| rtp.is_comfort_noise());
+    let prev_speech = rtp_packets.iter().rev().find(|rtp| rtp.talkspurt.is_some());
+
+    let mut is_start = rtp_info.packet.marker || after_comfort_noise;
+    if let Some(prev_speech) = prev_speech {
+        let is_consecutive =
+            prev_speech.packet.sequence_number.wrapping_add(1) == rtp_info.packet.sequence_number;
+        let timestamp_delta = rtp_info
+            .packet
+            .timestamp
+            .wrapping_sub(prev_speech.packet.timestamp);
+
+        if is_consecutive {
+            let is_jump = stats
+                .timestamp_delta
+                .is_some_and(|delta| timestamp_delta > delta.saturating_mul(2));
+            is_start |= is_jump;
+            if !is_jump {
+                stats.timestamp_delta = Some(timestamp_delta);
+            }
+        }
+        if !is_start {
+            stats.speech_time += rtp_info.time.saturating_sub(prev_speech.time);
+        }
+    } else {
+        is_start = true;
+    }
+
+    if is_start {
+        stats.talkspurts += 1;
+    }
+    rtp_info.talkspurt = Some(stats.talkspurts);
+    rtp_info.talkspurt_start = is_start;
+}
+
+fn update_dtmf_events(
+    dtmf_events: &mut Vec<DtmfEvent>,
+    payload_type: &PayloadType,
+    rtp_info: &RtpInfo,
+) {
+    let Some(PayloadInfo::TelephoneEvent(event)) = rtp_info.get_primary_payload_info() else {
+        return;
+    };
+
+    // all of the packets describing a single event have the same timestamp
+    let timestamp = rtp_info.packet.timestamp;
+    if let Some(last_event) = dtmf_events.last_mut() {
+        if last_event.rtp_timestamp == timestamp && last_event.event == event.event {
+            last_event.duration = max(last_event.duration, event.duration);
+            last_event.ended |= event.end;
+            return;
+        }
+    }
+
+    dtmf_events.push(DtmfEvent {
+        event: event.event,
+        time: rtp_info.time,
+        duration: event.duration,
+        clock_rate: payload_type.clock_rate,
+        volume: event.volume,
+        ended: event.end,
+        rtp_timestamp: timestamp,
+    });
+}
+
+fn snap_clock_rate(clock_rate: f64) -> Option<u32> {
+    let error = |common: &u32| (clock_rate / *common as f64 - 1.0).abs();
+    COMMON_CLOCK_RATES
+        .iter()
+        .min_by(|a, b| error(a).total_cmp(&error(b)))
+        .filter(|common| error(common) <= MAX_CLOCK_RATE_ERROR)
+        .copied()
+}
+
+fn resolve_payload_type(sdp: &Option<Sdp>, rtp: &RtpPacket) -> PayloadType {
+    if let Some(sdp) = sdp {
+        if let Some(pt) = sdp.payload_types.get(&rtp.payload_type.id) {
+            return pt.clone();
+        }
+    };
+
+    rtp.payload_type.clone()
+}
+
+fn is_rtx_payload_type(payload_type: &PayloadType) -> bool {
+    payload_type.name.eq_ignore_ascii_case("rtx")
+}
+
+fn resolve_payload_type_id(sdp: &Option<Sdp>, id: u8) -> PayloadType {
+    if let Some(sdp) = sdp {
+        if let Some(pt) = sdp.payload_types.get(&id) {
+            return pt.clone();
+        }
+    };
+
+    PayloadType::new(id)
+}
+
+fn get_payload_info(
+    sdp: &Option<Sdp>,
+    payload_type: &PayloadType,
+    packet: &Packet,
+    rtp: &RtpPacket,
+) -> Option<PayloadInfo> {
+    let payload = rtp.get_payload(packet)?;
+    let mut payload_info = PayloadInfo::build(payload_type, payload)?;
+
+    // payload type of the primary RED block is resolved the same way as the packet's one
+    if let PayloadInfo::Red(ref mut red) = payload_info {
+        let block = *red.get_primary_block();
+        let block_type = resolve_payload_type_id(sdp, block.payload_type);
+        let data = &payload[block.offset..block.offset + block.length];
+        red.primary = PayloadInfo::build(&block_type, data).map(Box::new);
+    }
+
+    Some(payload_info)
+}
+
+fn get_dependency_descriptor(sdp: &Option<Sdp>, rtp: &RtpPacket) -> Option<DependencyDescriptor> {
+    let id = sdp.as_ref()?.get_extension_id(DEPENDENCY_DESCRIPTOR_URI)?;
+    let data = rtp.get_extension(id)?;
+    DependencyDescriptor::build(data)
+}
+
+fn get_sdes_extension(sdp: &Option<Sdp>, rtp: &RtpPacket, uri: &str) -> Option<String> {
+    let id = sdp.as_ref()?.get_extension_id(uri)?;
+    let data = rtp.get_extension(id)?;
+    parse_sdes_value(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streams::test_utils::{build_rtp, build_stream, capture_time};
+
+    #[test]
+    fn counts_duplicate_reordered_and_late_packets() {
+        // sent every 20 ms, 2 is reordered, 4 duplicated, 5 late and 11 lost
+        let order = [
+            (0, 0),
+            (1, 20),
+            (3, 60),
+            (2, 70),
+            (4, 80),
+            (4, 85),
+            (6, 120),
+            (7, 140),
+            (8, 160),
+            (9, 180),
+            (10, 200),
+            (5, 230),
+            (12, 240),
+        ];
+        let packets: Vec<_> = order
+            .into_iter()
+            .map(|(sn, millis)| (capture_time(millis), build_rtp(sn, false)))
+            .collect();
+
+        let stream = build_stream(&packets);
+
+        assert_eq!(stream.duplicate_packets, 1);
+        assert_eq!(stream.reordered_packets, 1);
+        assert_eq!(stream.late_packets, 1);
+        assert_eq!(stream.get_expected_count(), 13);
+        assert_eq!(stream.get_lost_count(), 1);
+        let first = stream.rtp_packets[0].extended_sequence_number;
+        assert_eq!(
+            stream.get_lost_sequence_numbers(),
+            HashSet::from([first + 11])
+        );
+    }
+}
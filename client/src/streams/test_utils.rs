@@ -0,0 +1,77 @@
+use super::stream::Stream;
+use rtpeeker_common::packet::{SessionPacket, SessionProtocol, TransportProtocol};
+use rtpeeker_common::rtp::payload_type::PayloadType;
+use rtpeeker_common::rtp::RtpExtension;
+use rtpeeker_common::{Packet, RtpPacket};
+use std::time::Duration;
+
+// PCMU packet with 20 ms of audio per sequence number
+pub fn build_rtp(sequence_number: u16, marker: bool) -> RtpPacket {
+    RtpPacket {
+        version: 2,
+        padding: false,
+        extension: false,
+        marker,
+        payload_type: PayloadType::new(0),
+        sequence_number,
+        timestamp: sequence_number as u32 * 160,
+        ssrc: 0x1234,
+        csrc: Vec::new(),
+        extensions: Vec::new(),
+        payload_offset: 12,
+        payload_length: 160,
+    }
+}
+
+// with transport-wide sequence number offset by 100 from the RTP one, in extension 5
+pub fn build_twcc_rtp(sequence_number: u16) -> RtpPacket {
+    let mut rtp = build_rtp(sequence_number, false);
+    rtp.extension = true;
+    rtp.extensions = vec![RtpExtension {
+        id: 5,
+        data: (sequence_number + 100).to_be_bytes().to_vec(),
+    }];
+    rtp
+}
+
+pub fn build_packet(id: usize, time: Duration, rtp: &RtpPacket) -> Packet {
+    Packet {
+        payload: None,
+        id,
+        timestamp: time,
+        length: 200,
+        source_addr: "10.0.0.1:5000".parse().unwrap(),
+        destination_addr: "10.0.0.2:5000".parse().unwrap(),
+        transport_protocol: TransportProtocol::Udp,
+        session_protocol: SessionProtocol::Rtp,
+        contents: SessionPacket::Rtp(rtp.clone()),
+    }
+}
+
+// packets are captured in the given order, with ids following that order
+pub fn build_stream(packets: &[(Duration, RtpPacket)]) -> Stream {
+    let mut packets = packets
+        .iter()
+        .enumerate()
+        .map(|(id, (time, rtp))| (build_packet(id, *time, rtp), rtp));
+    let (packet, rtp) = packets.next().unwrap();
+    let mut stream = Stream::new(&packet, rtp, "A".to_string(), None);
+    for (packet, rtp) in packets {
+        stream.add_rtp_packet(&packet, rtp);
+    }
+    stream
+}
+
+// capture timestamps are absolute, so the tests don't start at zero
+pub fn capture_time(millis: u64) -> Duration {
+    Duration::from_secs(1_000) + Duration::from_millis(millis)
+}
+
+// packets captured on time, every 20 ms
+pub fn build_audio_stream(sequence_numbers: impl IntoIterator<Item = u16>) -> Stream {
+    let packets: Vec<_> = sequence_numbers
+        .into_iter()
+        .map(|sn| (capture_time(sn as u64 * 20), build_rtp(sn, false)))
+        .collect();
+    build_stream(&packets)
+}
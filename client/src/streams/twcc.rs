@@ -0,0 +1,375 @@
+use super::stream::{RtpInfo, Stream};
+use rtpeeker_common::rtcp::TransportFeedback;
+use rtpeeker_common::rtp::sequence::SequenceTracker;
+use rtpeeker_common::rtp::transport_cc::{parse_transport_sequence_number, TRANSPORT_CC_URI};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+
+// packets sent within this interval are a single group, as in Google Congestion Control,
+// see https://datatracker.ietf.org/doc/html/draft-ietf-rmcat-gcc-02#section-5.2
+const BURST_INTERVAL: f64 = 0.005;
+const BITRATE_WINDOW: f64 = 0.2;
+// used to infer the extension id without SDP
+const INFERENCE_PACKETS: usize = 500;
+const MIN_CONSECUTIVE_FRACTION: f64 = 0.9;
+
+// packet carrying the transport-wide sequence number
+#[derive(Debug)]
+pub struct TwccPacket {
+    // extended transport-wide sequence number
+    pub sequence_number: u32,
+    pub ssrc: u32,
+    pub bytes: usize,
+    // capture time, used as the send time
+    pub send_time: Duration,
+    // in microseconds, using the receiver's clock
+    pub receive_time: Option<i64>,
+    // reported in at least one feedback packet
+    pub is_reported: bool,
+    // in milliseconds, relative to the previous (received) packet in sequence number order
+    pub send_delta: Option<f64>,
+    pub receive_delta: Option<f64>,
+}
+
+impl TwccPacket {
+    pub fn is_reported_lost(&self) -> bool {
+        self.is_reported && self.receive_time.is_none()
+    }
+}
+
+// change of the one-way delay between consecutive groups of packets
+#[derive(Debug)]
+pub struct DelayGradient {
+    // send time of the group relative to the first packet on the transport, in seconds
+    pub time: f64,
+    // in milliseconds, positive when the delay increases
+    pub gradient: f64,
+}
+
+#[derive(Debug)]
+pub struct TwccStats {
+    pub extension_id: u8,
+    pub is_extension_id_inferred: bool,
+    pub ssrcs: Vec<u32>,
+    // sorted by the sequence number
+    pub packets: Vec<TwccPacket>,
+    pub feedbacks: usize,
+    pub first_time: Duration,
+    pub delay_gradients: Vec<DelayGradient>,
+    // aggregate sending bitrate of all RTP packets on the transport
+    // as (time relative to the first packet in seconds, kbps)
+    pub bitrates: Vec<(f64, f64)>,
+}
+
+impl TwccStats {
+    // `streams` have to share the transport,
+    // `feedbacks` are sent in the opposite direction, sorted by the arrival time
+    pub fn build(
+        streams: &[&Stream],
+        feedbacks: &[(Duration, &TransportFeedback)],
+    ) -> Option<Self> {
+        let mut rtp_packets: Vec<_> = streams
+            .iter()
+            .flat_map(|stream| stream.rtp_packets.iter())
+            .collect();
+        rtp_packets.sort_by_key(|rtp_info| rtp_info.id);
+        let first_time = rtp_packets.first()?.time;
+
+        let (extension_id, is_extension_id_inferred) = match streams
+            .iter()
+            .find_map(|stream| stream.get_extension_id(TRANSPORT_CC_URI))
+        {
+            Some(id) => (id, false),
+            None => (infer_extension_id(&rtp_packets)?, true),
+        };
+
+        let mut packets = get_packets(&rtp_packets, extension_id);
+        if packets.is_empty() {
+            return None;
+        }
+        apply_feedbacks(&mut packets, feedbacks);
+        packets.sort_by_key(|packet| packet.sequence_number);
+        update_deltas(&mut packets);
+
+        let mut ssrcs: Vec<_> = packets.iter().map(|packet| packet.ssrc).collect();
+        ssrcs.sort_unstable();
+        ssrcs.dedup();
+
+        Some(Self {
+            extension_id,
+            is_extension_id_inferred,
+            ssrcs,
+            delay_gradients: get_delay_gradients(&packets, first_time),
+            bitrates: get_bitrates(&rtp_packets, first_time),
+            packets,
+            feedbacks: feedbacks.len(),
+            first_time,
+        })
+    }
+
+    pub fn get_reported_count(&self) -> usize {
+        self.packets
+            .iter()
+            .filter(|packet| packet.is_reported)
+            .count()
+    }
+
+    pub fn get_reported_lost_count(&self) -> usize {
+        self.packets
+            .iter()
+            .filter(|packet| packet.is_reported_lost())
+            .count()
+    }
+
+    pub fn get_reported_loss(&self) -> Option<f64> {
+        let reported = self.get_reported_count();
+        if reported == 0 {
+            return None;
+        }
+        Some(self.get_reported_lost_count() as f64 / reported as f64)
+    }
+
+    pub fn get_max_delay_gradient(&self) -> Option<f64> {
+        self.delay_gradients
+            .iter()
+            .map(|delay_gradient| delay_gradient.gradient)
+            .max_by(f64::total_cmp)
+    }
+}
+
+// the id is the one of a two-byte extension incremented by one in most of the packets
+fn infer_extension_id(rtp_packets: &[&RtpInfo]) -> Option<u8> {
+    // id to (previous value, consecutive values, all values)
+    let mut candidates: BTreeMap<u8, (u16, usize, usize)> = BTreeMap::new();
+    for rtp_info in rtp_packets.iter().take(INFERENCE_PACKETS) {
+        for extension in &rtp_info.packet.extensions {
+            // 0 stands for extensions with other profiles
+            if extension.id == 0 {
+                continue;
+            }
+            let Some(value) = parse_transport_sequence_number(&extension.data) else {
+                continue;
+            };
+            let (previous, consecutive, count) =
+                candidates
+                    .entry(extension.id)
+                    .or_insert((value.wrapping_sub(2), 0, 0));
+            if value == previous.wrapping_add(1) {
+                *consecutive += 1;
+            }
+            *previous = value;
+            *count += 1;
+        }
+    }
+
+    candidates
+        .into_iter()
+        .filter(|(_, (_, consecutive, count))| {
+            *consecutive > 0 && *consecutive as f64 >= MIN_CONSECUTIVE_FRACTION * *count as f64
+        })
+        .max_by_key(|(_, (_, consecutive, _))| *consecutive)
+        .map(|(id, _)| id)
+}
+
+// in the order of arrival, duplicates are skipped
+fn get_packets(rtp_packets: &[&RtpInfo], extension_id: u8) -> Vec<TwccPacket> {
+    let mut tracker: Option<SequenceTracker> = None;
+    let mut sequence_numbers = HashSet::new();
+    let mut packets = Vec::new();
+
+    for rtp_info in rtp_packets {
+        let Some(sequence_number) = rtp_info
+            .packet
+            .get_extension(extension_id)
+            .and_then(parse_transport_sequence_number)
+        else {
+            continue;
+        };
+        let tracker = tracker.get_or_insert_with(|| SequenceTracker::new(sequence_number));
+        let (extended, _) = tracker.update(sequence_number);
+        if !sequence_numbers.insert(extended) {
+            continue;
+        }
+
+        packets.push(TwccPacket {
+            sequence_number: extended,
+            ssrc: rtp_info.packet.ssrc,
+            bytes: rtp_info.bytes,
+            send_time: rtp_info.time,
+            receive_time: None,
+            is_reported: false,
+            send_delta: None,
+            receive_delta: None,
+        });
+    }
+
+    packets
+}
+
+// feedback refers to the latest packet with the sequence number sent before it,
+// later feedbacks might report packets previously reported as lost
+fn apply_feedbacks(packets: &mut [TwccPacket], feedbacks: &[(Duration, &TransportFeedback)]) {
+    let mut latest: HashMap<u16, usize> = HashMap::new();
+    let mut next = 0;
+
+    for (time, feedback) in feedbacks {
+        while next < packets.len() && packets[next].send_time <= *time {
+            latest.insert(packets[next].sequence_number as u16, next);
+            next += 1;
+        }
+
+        for (offset, receive_time) in feedback.receive_times.iter().enumerate() {
+            let sequence_number = feedback.base_sequence_number.wrapping_add(offset as u16);
+            let Some(ix) = latest.get(&sequence_number) else {
+                continue;
+            };
+            let packet = &mut packets[*ix];
+            packet.is_reported = true;
+            if receive_time.is_some() {
+                packet.receive_time = *receive_time;
+            }
+        }
+    }
+}
+
+fn update_deltas(packets: &mut [TwccPacket]) {
+    let mut previous_send = None;
+    let mut previous_receive = None;
+
+    for packet in packets {
+        if let Some(previous) = previous_send {
+            packet.send_delta = Some(get_difference(packet.send_time, previous) * 1000.0);
+        }
+        previous_send = Some(packet.send_time);
+
+        let Some(receive_time) = packet.receive_time else {
+            continue;
+        };
+        if let Some(previous) = previous_receive {
+            packet.receive_delta = Some((receive_time - previous) as f64 / 1000.0);
+        }
+        previous_receive = Some(receive_time);
+    }
+}
+
+// groups are delimited by the send time of their first packet,
+// a group is represented by its latest send and arrival time
+fn get_delay_gradients(packets: &[TwccPacket], first_time: Duration) -> Vec<DelayGradient> {
+    let mut groups: Vec<(Duration, Duration, i64)> = Vec::new();
+    for packet in packets {
+        let Some(receive_time) = packet.receive_time else {
+            continue;
+        };
+        match groups.last_mut() {
+            Some((start, send, receive))
+                if get_difference(packet.send_time, *start) <= BURST_INTERVAL =>
+            {
+                *send = (*send).max(packet.send_time);
+                *receive = (*receive).max(receive_time);
+            }
+            _ => groups.push((packet.send_time, packet.send_time, receive_time)),
+        }
+    }
+
+    groups
+        .windows(2)
+        .map(|pair| {
+            let (_, previous_send, previous_receive) = pair[0];
+            let (_, send, receive) = pair[1];
+            let receive_delta = (receive - previous_receive) as f64 / 1000.0;
+            let send_delta = get_difference(send, previous_send) * 1000.0;
+            DelayGradient {
+                time: get_difference(send, first_time),
+                gradient: receive_delta - send_delta,
+            }
+        })
+        .collect()
+}
+
+fn get_bitrates(rtp_packets: &[&RtpInfo], first_time: Duration) -> Vec<(f64, f64)> {
+    let mut bins: Vec<usize> = Vec::new();
+    for rtp_info in rtp_packets {
+        let bin = (get_difference(rtp_info.time, first_time).max(0.0) / BITRATE_WINDOW) as usize;
+        if bins.len() <= bin {
+            bins.resize(bin + 1, 0);
+        }
+        bins[bin] += rtp_info.bytes;
+    }
+
+    bins.into_iter()
+        .enumerate()
+        .map(|(bin, bytes)| {
+            let time = bin as f64 * BITRATE_WINDOW;
+            (time, bytes as f64 * 8.0 / BITRATE_WINDOW / 1000.0)
+        })
+        .collect()
+}
+
+// in seconds, might be negative
+fn get_difference(time: Duration, other: Duration) -> f64 {
+    time.as_secs_f64() - other.as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streams::test_utils::{build_stream, build_twcc_rtp, capture_time};
+
+    #[test]
+    fn applies_feedback_to_sent_packets() {
+        // sent every 20 ms, 102 reported as lost, 105 and later not reported
+        let packets: Vec<_> = (0..10)
+            .map(|sn| (capture_time(sn as u64 * 20), build_twcc_rtp(sn)))
+            .collect();
+        let stream = build_stream(&packets);
+        let feedback = TransportFeedback {
+            sender_ssrc: 1,
+            media_ssrc: 0x1234,
+            base_sequence_number: 100,
+            feedback_count: 0,
+            receive_times: vec![Some(0), Some(20_000), None, Some(61_000), Some(80_000)],
+        };
+
+        let stats = TwccStats::build(&[&stream], &[(capture_time(200), &feedback)]).unwrap();
+
+        assert_eq!(stats.extension_id, 5);
+        assert!(stats.is_extension_id_inferred);
+        assert_eq!(stats.get_reported_count(), 5);
+        assert_eq!(stats.get_reported_lost_count(), 1);
+        assert!(stats.packets[2].is_reported_lost());
+        assert!(!stats.packets[5].is_reported);
+        let receive_deltas: Vec<_> = stats
+            .packets
+            .iter()
+            .take(5)
+            .map(|packet| packet.receive_delta)
+            .collect();
+        assert_eq!(
+            receive_deltas,
+            vec![None, Some(20.0), None, Some(41.0), Some(19.0)]
+        );
+        for packet in &stats.packets[1..] {
+            assert!((packet.send_delta.unwrap() - 20.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn ignores_feedback_sent_before_the_packet() {
+        let packets: Vec<_> = (0..10)
+            .map(|sn| (capture_time(sn as u64 * 20), build_twcc_rtp(sn)))
+            .collect();
+        let stream = build_stream(&packets);
+        let feedback = TransportFeedback {
+            sender_ssrc: 1,
+            media_ssrc: 0x1234,
+            base_sequence_number: 105,
+            feedback_count: 0,
+            receive_times: vec![Some(0)],
+        };
+
+        // 105 is sent at 100 ms
+        let stats = TwccStats::build(&[&stream], &[(capture_time(90), &feedback)]).unwrap();
+
+        assert_eq!(stats.get_reported_count(), 0);
+    }
+}
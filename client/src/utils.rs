@@ -1,5 +1,8 @@
 use chrono::{Duration, TimeZone, Utc};
 
+// seconds between the NTP epoch (1900) and the Unix epoch (1970)
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+
 fn ntp_to_datetime(ntp_time: u64) -> chrono::DateTime<Utc> {
     let int_part: i64 = (ntp_time >> 32).try_into().unwrap();
     let seconds = Duration::seconds(int_part);
@@ -26,6 +29,13 @@ pub fn ntp_to_f64(ntp_time: u64) -> f64 {
     seconds as f64 + fraction
 }
 
+// capture timestamp (since the Unix epoch) in the NTP format
+pub fn time_to_ntp(time: std::time::Duration) -> u64 {
+    let seconds = time.as_secs() + NTP_UNIX_OFFSET;
+    let fraction = ((time.subsec_nanos() as u64) << 32) / 1_000_000_000;
+    seconds << 32 | fraction
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,4 +60,12 @@ mod tests {
 
         assert_eq!(datetime, valid_datetime);
     }
+
+    #[test]
+    fn time_to_ntp_test() {
+        let time = std::time::Duration::from_millis(1_105_725_550_500);
+        let ntp_timestamp = time_to_ntp(time);
+
+        assert_eq!(ntp_timestamp, 0xc59286ee80000000);
+    }
 }
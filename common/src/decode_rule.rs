@@ -0,0 +1,58 @@
+use crate::packet::{Packet, SessionProtocol, TransportProtocol};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::SocketAddr;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleTarget {
+    // either source or destination port
+    Port(TransportProtocol, u16),
+    Flow(SocketAddr, SocketAddr, TransportProtocol),
+}
+
+impl RuleTarget {
+    pub fn matches(&self, packet: &Packet) -> bool {
+        match *self {
+            Self::Port(protocol, port) => {
+                packet.transport_protocol == protocol
+                    && (packet.source_addr.port() == port || packet.destination_addr.port() == port)
+            }
+            Self::Flow(source_addr, destination_addr, protocol) => {
+                packet.source_addr == source_addr
+                    && packet.destination_addr == destination_addr
+                    && packet.transport_protocol == protocol
+            }
+        }
+    }
+}
+
+impl fmt::Display for RuleTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Port(protocol, port) => write!(f, "{} port {}", protocol, port),
+            Self::Flow(source_addr, destination_addr, protocol) => {
+                write!(f, "{} {} → {}", protocol, source_addr, destination_addr)
+            }
+        }
+    }
+}
+
+// "decode as" rule, overrides the detection for all packets of the target
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DecodeRule {
+    pub target: RuleTarget,
+    pub protocol: SessionProtocol,
+}
+
+impl DecodeRule {
+    // the most recently added rule takes precedence
+    pub fn find<'a>(rules: &'a [Self], packet: &Packet) -> Option<&'a Self> {
+        rules.iter().rev().find(|rule| rule.target.matches(packet))
+    }
+}
+
+impl fmt::Display for DecodeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} as {}", self.target, self.protocol)
+    }
+}
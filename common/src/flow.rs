@@ -0,0 +1,170 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::packet::{Packet, SessionPacket, SessionProtocol};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::StreamKey;
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+// number of consecutive consistent packets required to consider a flow to be RTP
+pub const RTP_CONFIRMATION_PACKETS: usize = 4;
+// allows for some loss between consecutive packets
+const MAX_SEQUENCE_NUMBER_GAP: u16 = 100;
+// 10 seconds of 90 kHz clock, timestamps may go back a bit (e.g. B-frames, retransmissions)
+const MAX_TIMESTAMP_GAP: u32 = 900_000;
+// flows that are not confirmed in that time are forgotten, as most of the non-RTP
+// UDP packets look like a new flow (random bytes in place of the SSRC)
+#[cfg(not(target_arch = "wasm32"))]
+const CANDIDATE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// whether the packet plausibly follows the previous packet of the same RTP stream,
+// arguments are (sequence number, RTP timestamp) pairs
+pub fn is_consistent(previous: (u16, u32), current: (u16, u32)) -> bool {
+    let sequence_number_gap = current.0.wrapping_sub(previous.0);
+    let timestamp_gap = (current.1.wrapping_sub(previous.1) as i32).unsigned_abs();
+
+    (1..=MAX_SEQUENCE_NUMBER_GAP).contains(&sequence_number_gap)
+        && timestamp_gap <= MAX_TIMESTAMP_GAP
+}
+
+// per-flow state of the RTP detection, where a flow is a 5-tuple and the SSRC
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+struct RtpCandidate {
+    last: (u16, u32),
+    last_time: Duration,
+    consecutive: usize,
+    is_confirmed: bool,
+    // ids of packets that belong to the current run of consistent packets
+    pending: Vec<usize>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RtpCandidate {
+    fn new(sequence_number: u16, timestamp: u32, time: Duration) -> Self {
+        Self {
+            last: (sequence_number, timestamp),
+            last_time: time,
+            consecutive: 0,
+            is_confirmed: false,
+            pending: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, id: usize, sequence_number: u16, timestamp: u32, time: Duration) {
+        let current = (sequence_number, timestamp);
+        if self.consecutive > 0 && is_consistent(self.last, current) {
+            self.consecutive += 1;
+        } else {
+            self.consecutive = 1;
+            self.pending.clear();
+        }
+        self.last = current;
+        self.last_time = time;
+
+        if !self.is_confirmed {
+            self.pending.push(id);
+            self.is_confirmed = self.consecutive >= RTP_CONFIRMATION_PACKETS;
+        }
+    }
+}
+
+// classifies packets using the previous packets of the same flow,
+// a flow is considered RTP only after a few packets with the same SSRC
+// and sequence numbers and timestamps advancing consistently
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default)]
+pub struct FlowClassifier {
+    candidates: HashMap<StreamKey, RtpCandidate>,
+    last_eviction: Duration,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FlowClassifier {
+    // returns ids of the previous packets that should now be parsed as RTP,
+    // as the packet has just confirmed their flow
+    pub fn classify(&mut self, packet: &mut Packet) -> Vec<usize> {
+        self.evict_stale_candidates(packet.timestamp);
+
+        if packet.guess_rtcp() {
+            return Vec::new();
+        }
+
+        let Some(rtp) = packet.guess_rtp() else {
+            return Vec::new();
+        };
+
+        let key = (
+            packet.source_addr,
+            packet.destination_addr,
+            packet.transport_protocol,
+            rtp.ssrc,
+        );
+        let candidate = self.candidates.entry(key).or_insert_with(|| {
+            RtpCandidate::new(rtp.sequence_number, rtp.timestamp, packet.timestamp)
+        });
+        candidate.update(
+            packet.id,
+            rtp.sequence_number,
+            rtp.timestamp,
+            packet.timestamp,
+        );
+
+        if !candidate.is_confirmed {
+            return Vec::new();
+        }
+
+        packet.session_protocol = SessionProtocol::Rtp;
+        packet.contents = SessionPacket::Rtp(rtp);
+
+        // the current packet is already parsed
+        let mut pending = std::mem::take(&mut candidate.pending);
+        pending.retain(|id| *id != packet.id);
+        pending
+    }
+
+    // confirmed flows are kept, as there's only as many of them as there are RTP streams
+    fn evict_stale_candidates(&mut self, time: Duration) {
+        if time.saturating_sub(self.last_eviction) < CANDIDATE_TIMEOUT {
+            return;
+        }
+
+        self.candidates.retain(|_, candidate| {
+            candidate.is_confirmed || time.saturating_sub(candidate.last_time) < CANDIDATE_TIMEOUT
+        });
+        self.last_eviction = time;
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confirms_after_consistent_packets() {
+        let mut candidate = RtpCandidate::new(65534, 1000, Duration::ZERO);
+        for (id, sequence_number) in [65534, 65535, 0].into_iter().enumerate() {
+            candidate.update(id, sequence_number, 1000 + 960 * id as u32, Duration::ZERO);
+            assert!(!candidate.is_confirmed);
+        }
+        // single lost packet
+        candidate.update(3, 2, 1000 + 960 * 4, Duration::ZERO);
+
+        assert!(candidate.is_confirmed);
+        assert_eq!(candidate.pending, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn resets_on_inconsistent_packet() {
+        let mut candidate = RtpCandidate::new(100, 1000, Duration::ZERO);
+        candidate.update(0, 100, 1000, Duration::ZERO);
+        candidate.update(1, 101, 1960, Duration::ZERO);
+        candidate.update(2, 7, 1960, Duration::ZERO);
+
+        assert_eq!(candidate.consecutive, 1);
+        assert_eq!(candidate.pending, vec![2]);
+        assert!(!candidate.is_confirmed);
+    }
+}
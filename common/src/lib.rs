@@ -3,12 +3,17 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::net::SocketAddr;
 
+pub use crate::decode_rule::DecodeRule;
+pub use crate::quality_rule::QualityRule;
 pub use crate::rtcp::RtcpPacket;
 pub use crate::rtp::RtpPacket;
 pub use packet::Packet;
 pub use sdp::Sdp;
 
+pub mod decode_rule;
+pub mod flow;
 pub mod packet;
+pub mod quality_rule;
 pub mod rtcp;
 pub mod rtp;
 pub mod sdp;
@@ -56,6 +61,12 @@ pub enum Request {
     Reparse(usize, packet::SessionProtocol),
     ChangeSource(Source),
     ParseSdp(StreamKey, String),
+    RemoveSdp(StreamKey),
+    // replaces the rule with the same target, if there is one
+    AddDecodeRule(DecodeRule),
+    RemoveDecodeRule(decode_rule::RuleTarget),
+    // replaces all of the quality rules, which are shared by all of the sources
+    SetQualityRules(Vec<QualityRule>),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -63,6 +74,9 @@ pub enum Response {
     Packet(Packet),
     Sources(Vec<Source>),
     Sdp(StreamKey, Sdp),
+    SdpRemoved(StreamKey),
+    DecodeRules(Vec<DecodeRule>),
+    QualityRules(Vec<QualityRule>),
 }
 
 impl Request {
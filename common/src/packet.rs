@@ -107,30 +107,37 @@ impl Packet {
         })
     }
 
-    pub fn guess_payload(&mut self) {
+    // RTCP packets are distinguishable on their own, unlike RTP,
+    // which is detected per flow, see `FlowClassifier`
+    pub fn guess_rtcp(&mut self) -> bool {
         // could use port to determine validity
-        // TODO: STUN data, TURN channels, RTCP
+        // TODO: STUN data, TURN channels
         //
         // also, some UDP ports are used by other protocols
         // see Wireshark -> View -> Internals -> Dissector Table -> UDP port
         if self.transport_protocol != TransportProtocol::Udp {
-            return;
+            return false;
         }
 
-        if let Some(rtcp) = RtcpPacket::build(self) {
-            if is_rtcp(&rtcp) {
-                self.session_protocol = SessionProtocol::Rtcp;
-                self.contents = SessionPacket::Rtcp(rtcp);
-                return;
-            }
+        let Some(rtcp) = RtcpPacket::build(self) else {
+            return false;
+        };
+        if !is_rtcp(&rtcp) {
+            return false;
         }
 
-        if let Some(rtp) = RtpPacket::build(self) {
-            if is_rtp(&rtp) {
-                self.session_protocol = SessionProtocol::Rtp;
-                self.contents = SessionPacket::Rtp(rtp);
-            }
+        self.session_protocol = SessionProtocol::Rtcp;
+        self.contents = SessionPacket::Rtcp(rtcp);
+        true
+    }
+
+    // RTP packet candidate, doesn't change the packet's contents
+    pub fn guess_rtp(&self) -> Option<RtpPacket> {
+        if self.transport_protocol != TransportProtocol::Udp {
+            return None;
         }
+
+        RtpPacket::build(self).filter(is_rtp)
     }
 
     pub fn parse_as(&mut self, packet_type: SessionProtocol) {
@@ -183,6 +190,7 @@ fn is_rtcp(packets: &[RtcpPacket]) -> bool {
         RtcpPacket::SenderReport(_)
             | RtcpPacket::ReceiverReport(_)
             | RtcpPacket::Goodbye(_)
+            | RtcpPacket::TransportFeedback(_)
             | RtcpPacket::PayloadSpecificFeedback
     ) {
         return false;
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// condition evaluated against each stream as its packets arrive
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum QualityRule {
+    // interarrival jitter above the threshold in milliseconds
+    Jitter(f64),
+    // percentage of lost packets above the threshold over the window in seconds
+    Loss(f64, f64),
+    // no packets for longer than the threshold in milliseconds
+    Gap(f64),
+    // new SSRC sending the same payload type over the same transport
+    SsrcChange,
+}
+
+impl QualityRule {
+    // parses rules such as "jitter > 30 ms", "loss > 2% over 5 s",
+    // "no packets for 500 ms" or "SSRC changed", case insensitive
+    pub fn from_string(rule_str: &str) -> Option<Self> {
+        let rule_str = rule_str.trim().to_lowercase();
+
+        if rule_str == "ssrc changed" {
+            return Some(Self::SsrcChange);
+        }
+
+        if let Some(threshold) = strip_condition(&rule_str, "jitter") {
+            return Some(Self::Jitter(parse_quantity(threshold, "ms")?));
+        }
+
+        if let Some(threshold) = rule_str.strip_prefix("no packets for") {
+            return Some(Self::Gap(parse_quantity(threshold, "ms")?));
+        }
+
+        if let Some(condition) = strip_condition(&rule_str, "loss") {
+            let (threshold, window) = condition.split_once("over")?;
+            let window = parse_quantity(window, "s")?;
+            if window == 0.0 {
+                return None;
+            }
+            return Some(Self::Loss(parse_quantity(threshold, "%")?, window));
+        }
+
+        None
+    }
+
+    // one rule per line, empty lines and lines starting with '#' are skipped,
+    // returns the number of the first invalid line on failure
+    pub fn parse_rules(rules_str: &str) -> Result<Vec<Self>, usize> {
+        rules_str
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#')
+            })
+            .map(|(ix, line)| Self::from_string(line).ok_or(ix + 1))
+            .collect()
+    }
+}
+
+impl fmt::Display for QualityRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Jitter(threshold) => write!(f, "jitter > {} ms", threshold),
+            Self::Loss(threshold, window) => write!(f, "loss > {}% over {} s", threshold, window),
+            Self::Gap(threshold) => write!(f, "no packets for {} ms", threshold),
+            Self::SsrcChange => write!(f, "SSRC changed"),
+        }
+    }
+}
+
+// "<name> > <rest>" to the rest
+fn strip_condition<'a>(rule_str: &'a str, name: &str) -> Option<&'a str> {
+    rule_str.strip_prefix(name)?.trim_start().strip_prefix('>')
+}
+
+// non-negative number followed by the unit, optionally separated with whitespace
+fn parse_quantity(quantity_str: &str, unit: &str) -> Option<f64> {
+    let value = quantity_str.trim().strip_suffix(unit)?.trim();
+    let value: f64 = value.parse().ok()?;
+
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_string_parses_rules() {
+        let rules = [
+            ("jitter > 30 ms", QualityRule::Jitter(30.0)),
+            ("Jitter > 12.5ms", QualityRule::Jitter(12.5)),
+            ("loss > 2% over 5 s", QualityRule::Loss(2.0, 5.0)),
+            ("loss>0.5 % over 10s", QualityRule::Loss(0.5, 10.0)),
+            ("no packets for 500 ms", QualityRule::Gap(500.0)),
+            ("SSRC changed", QualityRule::SsrcChange),
+        ];
+
+        for (rule_str, rule) in rules {
+            assert_eq!(
+                QualityRule::from_string(rule_str),
+                Some(rule),
+                "{}",
+                rule_str
+            );
+        }
+    }
+
+    #[test]
+    fn from_string_rejects_invalid_rules() {
+        let rules = [
+            "jitter > 30",
+            "jitter > -1 ms",
+            "loss > 2%",
+            "loss > 2% over 0 s",
+            "no packets for 1 s",
+            "bitrate < 100 kbps",
+        ];
+
+        for rule_str in rules {
+            assert_eq!(QualityRule::from_string(rule_str), None, "{}", rule_str);
+        }
+    }
+
+    #[test]
+    fn display_is_parsable() {
+        let rules = [
+            QualityRule::Jitter(30.0),
+            QualityRule::Loss(2.5, 5.0),
+            QualityRule::Gap(500.0),
+            QualityRule::SsrcChange,
+        ];
+
+        for rule in rules {
+            assert_eq!(QualityRule::from_string(&rule.to_string()), Some(rule));
+        }
+    }
+
+    #[test]
+    fn parse_rules_skips_comments() {
+        let rules_str = "# thresholds\njitter > 30 ms\n\n  # gaps\nno packets for 500 ms\n";
+        let rules = QualityRule::parse_rules(rules_str);
+
+        assert_eq!(
+            rules,
+            Ok(vec![QualityRule::Jitter(30.0), QualityRule::Gap(500.0)])
+        );
+    }
+
+    #[test]
+    fn parse_rules_reports_invalid_line() {
+        let rules_str = "jitter > 30 ms\n# comment\njitter above 30 ms\n";
+
+        assert_eq!(QualityRule::parse_rules(rules_str), Err(3));
+    }
+}
@@ -4,12 +4,14 @@ pub use reception_report::ReceptionReport;
 pub use sender_report::SenderReport;
 use serde::{Deserialize, Serialize};
 pub use source_description::SourceDescription;
+pub use transport_feedback::TransportFeedback;
 
 pub mod goodbye;
 pub mod receiver_report;
 pub mod reception_report;
 pub mod sender_report;
 pub mod source_description;
+pub mod transport_feedback;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RtcpPacket {
@@ -17,6 +19,7 @@ pub enum RtcpPacket {
     ReceiverReport(receiver_report::ReceiverReport),
     SourceDescription(source_description::SourceDescription),
     Goodbye(goodbye::Goodbye),
+    TransportFeedback(transport_feedback::TransportFeedback),
     ApplicationDefined,
     PayloadSpecificFeedback,
     TransportSpecificFeedback,
@@ -33,6 +36,7 @@ impl RtcpPacket {
             ReceiverReport(_) => "Receiver Report",
             SourceDescription(_) => "Source Description",
             Goodbye(_) => "Goodbye",
+            TransportFeedback(_) => "Transport-wide CC Feedback",
             ApplicationDefined => "Application Defined",
             PayloadSpecificFeedback => "Payload-specific Feedback",
             TransportSpecificFeedback => "Transport-specific Feedback",
@@ -74,6 +78,7 @@ impl RtcpPacket {
         use rtcp::receiver_report::ReceiverReport;
         use rtcp::sender_report::SenderReport;
         use rtcp::source_description::SourceDescription;
+        use rtcp::transport_feedbacks::transport_layer_cc::TransportLayerCc;
 
         let packet = packet.as_any();
 
@@ -93,6 +98,10 @@ impl RtcpPacket {
             return RtcpPacket::SourceDescription(source_description::SourceDescription::new(pack));
         }
 
+        if let Some(pack) = packet.downcast_ref::<TransportLayerCc>() {
+            return RtcpPacket::TransportFeedback(transport_feedback::TransportFeedback::new(pack));
+        }
+
         RtcpPacket::Other
     }
 }
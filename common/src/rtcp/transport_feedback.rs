@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+// reference time is expressed in multiples of 64 ms
+#[cfg(not(target_arch = "wasm32"))]
+const REFERENCE_TIME_UNIT: i64 = 64_000;
+
+// transport-wide congestion control feedback (TWCC),
+// see https://datatracker.ietf.org/doc/html/draft-holmer-rmcat-transport-wide-cc-extensions-01
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransportFeedback {
+    pub sender_ssrc: u32,
+    pub media_ssrc: u32,
+    pub base_sequence_number: u16,
+    pub feedback_count: u8,
+    // receive time (in microseconds, using the receiver's clock) of consecutive packets
+    // starting from the base sequence number, `None` if the packet was not received
+    pub receive_times: Vec<Option<i64>>,
+}
+
+impl TransportFeedback {
+    pub fn get_received_count(&self) -> usize {
+        self.receive_times.iter().flatten().count()
+    }
+
+    pub fn get_lost_count(&self) -> usize {
+        self.receive_times.len() - self.get_received_count()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TransportFeedback {
+    pub fn new(packet: &rtcp::transport_feedbacks::transport_layer_cc::TransportLayerCc) -> Self {
+        use rtcp::transport_feedbacks::transport_layer_cc::{PacketStatusChunk, SymbolTypeTcc};
+
+        let statuses = packet
+            .packet_chunks
+            .iter()
+            .flat_map(|chunk| match chunk {
+                PacketStatusChunk::RunLengthChunk(chunk) => {
+                    vec![chunk.packet_status_symbol; chunk.run_length as usize]
+                }
+                PacketStatusChunk::StatusVectorChunk(chunk) => chunk.symbol_list.clone(),
+            })
+            // the last chunk might be padded with additional symbols
+            .take(packet.packet_status_count as usize)
+            .map(|symbol| match symbol {
+                SymbolTypeTcc::PacketNotReceived => None,
+                SymbolTypeTcc::PacketReceivedWithoutDelta => Some(false),
+                _ => Some(true),
+            });
+
+        let deltas: Vec<_> = packet.recv_deltas.iter().map(|delta| delta.delta).collect();
+
+        Self::build(
+            packet.sender_ssrc,
+            packet.media_ssrc,
+            packet.base_sequence_number,
+            packet.fb_pkt_count,
+            packet.reference_time,
+            statuses,
+            &deltas,
+        )
+    }
+
+    // `statuses` are `None` for lost packets and `Some(has_delta)` for received ones,
+    // `deltas` (in microseconds) are present only for the packets received with a delta
+    fn build(
+        sender_ssrc: u32,
+        media_ssrc: u32,
+        base_sequence_number: u16,
+        feedback_count: u8,
+        reference_time: u32,
+        statuses: impl Iterator<Item = Option<bool>>,
+        deltas: &[i64],
+    ) -> Self {
+        let mut time = reference_time as i64 * REFERENCE_TIME_UNIT;
+        let mut deltas = deltas.iter();
+
+        let receive_times = statuses
+            .map(|status| {
+                let has_delta = status?;
+                if has_delta {
+                    time += deltas.next().copied().unwrap_or(0);
+                }
+                Some(time)
+            })
+            .collect();
+
+        Self {
+            sender_ssrc,
+            media_ssrc,
+            base_sequence_number,
+            feedback_count,
+            receive_times,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_assigns_cumulative_receive_times() {
+        let statuses = [Some(true), None, Some(false), Some(true)];
+        let feedback =
+            TransportFeedback::build(1, 2, 100, 3, 2, statuses.into_iter(), &[250, 1000]);
+
+        assert_eq!(
+            feedback.receive_times,
+            vec![Some(128_250), None, Some(128_250), Some(129_250)]
+        );
+        assert_eq!(feedback.get_received_count(), 3);
+        assert_eq!(feedback.get_lost_count(), 1);
+    }
+
+    #[test]
+    fn build_handles_negative_deltas() {
+        let statuses = [Some(true), Some(true)];
+        let feedback = TransportFeedback::build(1, 2, 0, 0, 0, statuses.into_iter(), &[500, -250]);
+
+        assert_eq!(feedback.receive_times, vec![Some(500), Some(250)]);
+    }
+}
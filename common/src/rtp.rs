@@ -1,7 +1,24 @@
 use payload_type::PayloadType;
 use serde::{Deserialize, Serialize};
 
+pub mod av1;
+pub mod comfort_noise;
+pub mod dependency_descriptor;
+pub mod fec;
+pub mod h264;
+pub mod h265;
+pub mod mpeg_ts;
+pub mod opus;
+pub mod payload;
 pub mod payload_type;
+pub mod red;
+pub mod rtx;
+pub mod sequence;
+pub mod stream_id;
+pub mod telephone_event;
+pub mod transport_cc;
+pub mod vp8;
+pub mod vp9;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RtpPacket {
@@ -14,7 +31,31 @@ pub struct RtpPacket {
     pub timestamp: u32,
     pub ssrc: u32,
     pub csrc: Vec<u32>,
-    pub payload_length: usize, // extension information skipped
+    pub extensions: Vec<RtpExtension>,
+    pub payload_offset: usize,
+    pub payload_length: usize,
+}
+
+// element of one-byte or two-byte header extension (RFC 8285),
+// extensions with other profiles are represented as a single element with id 0
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RtpExtension {
+    pub id: u8,
+    pub data: Vec<u8>,
+}
+
+impl RtpPacket {
+    pub fn get_extension(&self, id: u8) -> Option<&[u8]> {
+        self.extensions
+            .iter()
+            .find(|extension| extension.id == id)
+            .map(|extension| extension.data.as_slice())
+    }
+
+    pub fn get_payload<'a>(&self, packet: &'a super::Packet) -> Option<&'a [u8]> {
+        let end = self.payload_offset + self.payload_length;
+        packet.payload.as_ref()?.get(self.payload_offset..end)
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -25,14 +66,31 @@ impl RtpPacket {
 
         // payload field should never be empty
         // except for when encoding the packet
-        let mut buffer: &[u8] = packet
+        let raw_packet: &[u8] = packet
             .payload
             .as_ref()
             .expect("Packet's payload field is empty");
+        let mut buffer = raw_packet;
         let Ok(Packet { header, payload }) = Packet::unmarshal(&mut buffer) else {
             return None;
         };
 
+        // padding is stripped from the payload by `unmarshal`
+        let padding_length = match header.padding {
+            true => *raw_packet.last().unwrap() as usize,
+            false => 0,
+        };
+        let payload_offset = raw_packet.len() - padding_length - payload.len();
+
+        let extensions = header
+            .extensions
+            .iter()
+            .map(|extension| RtpExtension {
+                id: extension.id,
+                data: extension.payload.to_vec(),
+            })
+            .collect();
+
         Some(Self {
             version: header.version,
             padding: header.padding,
@@ -43,6 +101,8 @@ impl RtpPacket {
             timestamp: header.timestamp,
             ssrc: header.ssrc,
             csrc: header.csrc,
+            extensions,
+            payload_offset,
             payload_length: payload.len(),
         })
     }
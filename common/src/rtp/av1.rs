@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const SEQUENCE_HEADER: u8 = 1;
+
+// RTP payload format for AV1, see https://aomediacodec.github.io/av1-rtp-spec/
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Av1Payload {
+    // Z, first OBU element is a continuation of an OBU from the previous packet
+    pub continuation: bool,
+    // Y, last OBU element will continue in the next packet
+    pub continues: bool,
+    // W, number of OBU elements, 0 if each element is preceded by its length
+    pub obu_count: u8,
+    // N, first packet of a coded video sequence
+    pub new_sequence: bool,
+    // headers of OBUs starting in this packet
+    pub obus: Vec<Av1Obu>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Av1Obu {
+    pub obu_type: u8,
+    pub temporal_id: Option<u8>,
+    pub spatial_id: Option<u8>,
+    pub size: usize,
+}
+
+impl Av1Payload {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        let header = *payload.first()?;
+        let continuation = header & 0x80 != 0;
+        let continues = header & 0x40 != 0;
+        let obu_count = (header >> 4) & 0x03;
+        let new_sequence = header & 0x08 != 0;
+
+        let mut obus = Vec::new();
+        let mut rest = &payload[1..];
+        let mut element_ix = 0;
+        while !rest.is_empty() {
+            let is_last = obu_count != 0 && element_ix + 1 == obu_count as usize;
+            let element = if is_last {
+                std::mem::take(&mut rest)
+            } else {
+                let (length, length_size) = read_leb128(rest)?;
+                let element = rest.get(length_size..length_size + length)?;
+                rest = &rest[length_size + length..];
+                element
+            };
+
+            // continued fragment does not contain the OBU header
+            let is_continued = element_ix == 0 && continuation;
+            if !is_continued && !element.is_empty() {
+                obus.push(Av1Obu::new(element)?);
+            }
+            element_ix += 1;
+        }
+
+        Some(Self {
+            continuation,
+            continues,
+            obu_count,
+            new_sequence,
+            obus,
+        })
+    }
+
+    pub fn has_sequence_header(&self) -> bool {
+        self.obus.iter().any(|obu| obu.obu_type == SEQUENCE_HEADER)
+    }
+
+    pub fn is_keyframe(&self) -> bool {
+        self.new_sequence
+    }
+
+    pub fn get_details(&self) -> String {
+        let mut details = format!(
+            "Continuation (Z): {}\nContinues (Y): {}\nOBU count (W): {}\nNew coded video sequence (N): {}\n",
+            self.continuation, self.continues, self.obu_count, self.new_sequence
+        );
+        for obu in &self.obus {
+            details.push_str(&format!(
+                "OBU: {} (type {}, {} bytes",
+                obu_type_name(obu.obu_type),
+                obu.obu_type,
+                obu.size
+            ));
+            if let (Some(temporal_id), Some(spatial_id)) = (obu.temporal_id, obu.spatial_id) {
+                details.push_str(&format!(", S{}T{}", spatial_id, temporal_id));
+            }
+            details.push_str(")\n");
+        }
+        details
+    }
+}
+
+impl Av1Obu {
+    fn new(element: &[u8]) -> Option<Self> {
+        let header = *element.first()?;
+        let has_extension = header & 0x04 != 0;
+
+        let (temporal_id, spatial_id) = if has_extension {
+            let extension = *element.get(1)?;
+            (Some(extension >> 5), Some((extension >> 3) & 0x03))
+        } else {
+            (None, None)
+        };
+
+        Some(Self {
+            obu_type: (header >> 3) & 0x0f,
+            temporal_id,
+            spatial_id,
+            size: element.len(),
+        })
+    }
+}
+
+impl fmt::Display for Av1Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let obu_types = self
+            .obus
+            .iter()
+            .map(|obu| obu_type_name(obu.obu_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "AV1 [{}]", obu_types)?;
+        if self.continuation {
+            write!(f, " (cont.)")?;
+        }
+        if self.new_sequence {
+            write!(f, " new sequence")?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn obu_type_name(obu_type: u8) -> &'static str {
+    match obu_type {
+        SEQUENCE_HEADER => "Sequence header",
+        2 => "Temporal delimiter",
+        3 => "Frame header",
+        4 => "Tile group",
+        5 => "Metadata",
+        6 => "Frame",
+        7 => "Redundant frame header",
+        8 => "Tile list",
+        15 => "Padding",
+        _ => "Reserved",
+    }
+}
+
+fn read_leb128(bytes: &[u8]) -> Option<(usize, usize)> {
+    let mut value = 0;
+    for (ix, byte) in bytes.iter().take(8).enumerate() {
+        value |= ((byte & 0x7f) as usize) << (ix * 7);
+        if byte & 0x80 == 0 {
+            return Some((value, ix + 1));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_aggregated_obus() {
+        let payload = [
+            0x28, // W = 2, N
+            0x03, 0x08, 0x00, 0x00, // sequence header with length
+            0x32, 0x11, 0x22, // frame, last element without length
+        ];
+        let av1 = Av1Payload::build(&payload).unwrap();
+
+        assert_eq!(av1.obus.len(), 2);
+        assert!(av1.has_sequence_header());
+        assert!(av1.is_keyframe());
+        assert_eq!(av1.obus[1].obu_type, 6);
+    }
+
+    #[test]
+    fn skips_continued_fragment() {
+        let payload = [0xc0, 0x02, 0xaa, 0xbb, 0x01, 0x10];
+        let av1 = Av1Payload::build(&payload).unwrap();
+
+        assert_eq!(av1.obus.len(), 1);
+        assert_eq!(av1.obus[0].obu_type, 2);
+    }
+}
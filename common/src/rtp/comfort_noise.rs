@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Comfort Noise payload, see RFC 3389, section 3
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ComfortNoise {
+    pub noise_level: u8, // in -dBov
+    pub reflection_coefficients: Vec<u8>,
+}
+
+impl ComfortNoise {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        let (&level, coefficients) = payload.split_first()?;
+
+        Some(Self {
+            noise_level: level & 0x7f,
+            reflection_coefficients: coefficients.to_vec(),
+        })
+    }
+
+    pub fn get_details(&self) -> String {
+        format!(
+            "Noise level: -{} dBov\nSpectral model order: {}\n",
+            self.noise_level,
+            self.reflection_coefficients.len()
+        )
+    }
+}
+
+impl fmt::Display for ComfortNoise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CN -{} dBov", self.noise_level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_noise_level_and_coefficients() {
+        let cn = ComfortNoise::build(&[0x40, 0x81, 0x7f]).unwrap();
+
+        assert_eq!(cn.noise_level, 64);
+        assert_eq!(cn.reflection_coefficients, vec![0x81, 0x7f]);
+    }
+}
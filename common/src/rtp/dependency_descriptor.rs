@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub const DEPENDENCY_DESCRIPTOR_URI: &str =
+    "https://aomediacodec.github.io/av1-rtp-spec/#dependency-descriptor-rtp-header-extension";
+
+// Dependency Descriptor RTP header extension,
+// see https://aomediacodec.github.io/av1-rtp-spec/#dependency-descriptor-rtp-header-extension
+// only the mandatory fields and the beginning of the extended fields are decoded
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DependencyDescriptor {
+    pub start_of_frame: bool,
+    pub end_of_frame: bool,
+    pub frame_dependency_template_id: u8,
+    pub frame_number: u16,
+    pub template_structure: Option<TemplateStructure>,
+    pub active_decode_targets_present: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct TemplateStructure {
+    pub template_id_offset: u8,
+    pub decode_target_count: u8,
+}
+
+impl DependencyDescriptor {
+    pub fn build(data: &[u8]) -> Option<Self> {
+        let &[first, frame_high, frame_low, ..] = data else {
+            return None;
+        };
+
+        let mut template_structure = None;
+        let mut active_decode_targets_present = false;
+        if data.len() > 3 {
+            let mut reader = BitReader::new(&data[3..]);
+            let template_structure_present = reader.read(1)? == 1;
+            active_decode_targets_present = reader.read(1)? == 1;
+            // custom_dtis, custom_fdiffs and custom_chains flags
+            reader.read(3)?;
+
+            if template_structure_present {
+                template_structure = Some(TemplateStructure {
+                    template_id_offset: reader.read(6)? as u8,
+                    decode_target_count: reader.read(5)? as u8 + 1,
+                });
+            }
+        }
+
+        Some(Self {
+            start_of_frame: first & 0x80 != 0,
+            end_of_frame: first & 0x40 != 0,
+            frame_dependency_template_id: first & 0x3f,
+            frame_number: u16::from_be_bytes([frame_high, frame_low]),
+            template_structure,
+            active_decode_targets_present,
+        })
+    }
+
+    pub fn is_keyframe(&self) -> bool {
+        // template structure is sent with every keyframe
+        self.start_of_frame && self.template_structure.is_some()
+    }
+
+    pub fn get_details(&self) -> String {
+        let mut details = format!(
+            "Dependency descriptor:\n  Start of frame: {}\n  End of frame: {}\n  Template ID: {}\n  Frame number: {}\n",
+            self.start_of_frame,
+            self.end_of_frame,
+            self.frame_dependency_template_id,
+            self.frame_number
+        );
+        if let Some(structure) = self.template_structure {
+            details.push_str(&format!(
+                "  Template structure: ID offset {}, {} decode targets\n",
+                structure.template_id_offset, structure.decode_target_count
+            ));
+        }
+        details
+    }
+}
+
+impl fmt::Display for DependencyDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DD frame {} template {}",
+            self.frame_number, self.frame_dependency_template_id
+        )
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn read(&mut self, bits: usize) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = self.data.get(self.position / 8)?;
+            let bit = (byte >> (7 - self.position % 8)) & 0x01;
+            value = (value << 1) | bit as u32;
+            self.position += 1;
+        }
+
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_descriptor_with_template_structure() {
+        // start of frame, template 1, frame 258, template structure present
+        // with ID offset 1 and 3 decode targets
+        let data = [0x81, 0x01, 0x02, 0x80, 0x22];
+        let descriptor = DependencyDescriptor::build(&data).unwrap();
+
+        assert!(descriptor.start_of_frame);
+        assert_eq!(descriptor.frame_number, 258);
+        let structure = descriptor.template_structure.unwrap();
+        assert_eq!(structure.template_id_offset, 1);
+        assert_eq!(structure.decode_target_count, 3);
+        assert!(descriptor.is_keyframe());
+    }
+}
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum FecScheme {
+    Ulpfec,
+    Flexfec,
+}
+
+impl fmt::Display for FecScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            Self::Ulpfec => "ULPFEC",
+            Self::Flexfec => "FlexFEC",
+        };
+
+        write!(f, "{}", res)
+    }
+}
+
+// FEC header and the protected packets,
+// see RFC 5109, section 7 (ULPFEC) and RFC 8627, section 4.2 (FlexFEC)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FecPayload {
+    pub scheme: FecScheme,
+    // None if protected packets are in the same stream (ULPFEC)
+    // or the SSRC is not in the FEC header (FlexFEC, RFC 8627)
+    pub protected_ssrc: Option<u32>,
+    pub sequence_number_base: u16,
+    pub protected_sequence_numbers: Vec<u16>,
+    pub timestamp_recovery: u32,
+    pub length_recovery: u16,
+}
+
+impl FecPayload {
+    pub fn build_ulpfec(payload: &[u8]) -> Option<Self> {
+        let header = payload.get(..10)?;
+        let long_mask = header[0] & 0x40 != 0;
+        let sequence_number_base = u16::from_be_bytes([header[2], header[3]]);
+        let timestamp_recovery = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let length_recovery = u16::from_be_bytes([header[8], header[9]]);
+
+        // only the first (most important) protection level is considered
+        let mask_length = if long_mask { 6 } else { 2 };
+        let mask = payload.get(12..12 + mask_length)?;
+        let protected_sequence_numbers = mask_to_sequence_numbers(mask, sequence_number_base);
+
+        Some(Self {
+            scheme: FecScheme::Ulpfec,
+            protected_ssrc: None,
+            sequence_number_base,
+            protected_sequence_numbers,
+            timestamp_recovery,
+            length_recovery,
+        })
+    }
+
+    // SN base follows the 8-byte header directly, the protected SSRCs are not
+    // carried in the FEC header, but in the CSRC list of the RTP header,
+    // or they have to be taken from `a=ssrc-group:FEC-FR` in SDP
+    pub fn build_flexfec(payload: &[u8]) -> Option<Self> {
+        let header = payload.get(..8)?;
+        let (length_recovery, timestamp_recovery) = get_flexfec_recovery(header);
+
+        // only the first protected SSRC is considered
+        let protected = payload.get(8..)?;
+        let (sequence_number_base, protected_sequence_numbers) =
+            get_flexfec_protected(header, protected)?;
+
+        Some(Self {
+            scheme: FecScheme::Flexfec,
+            protected_ssrc: None,
+            sequence_number_base,
+            protected_sequence_numbers,
+            timestamp_recovery,
+            length_recovery,
+        })
+    }
+
+    // draft-ietf-payload-flexible-fec-scheme-03 (used by libwebrtc) carries
+    // the number of protected SSRCs and each protected SSRC before its SN base
+    pub fn build_flexfec_03(payload: &[u8]) -> Option<Self> {
+        let header = payload.get(..12)?;
+        let (length_recovery, timestamp_recovery) = get_flexfec_recovery(header);
+
+        // only the first protected SSRC is considered
+        let protected_ssrc = payload.get(12..16)?;
+        let protected_ssrc = u32::from_be_bytes([
+            protected_ssrc[0],
+            protected_ssrc[1],
+            protected_ssrc[2],
+            protected_ssrc[3],
+        ]);
+        let protected = payload.get(16..)?;
+        let (sequence_number_base, protected_sequence_numbers) =
+            get_flexfec_protected(header, protected)?;
+
+        Some(Self {
+            scheme: FecScheme::Flexfec,
+            protected_ssrc: Some(protected_ssrc),
+            sequence_number_base,
+            protected_sequence_numbers,
+            timestamp_recovery,
+            length_recovery,
+        })
+    }
+
+    pub fn get_details(&self) -> String {
+        let mut details = format!(
+            "Scheme: {}\nSN base: {}\nTimestamp recovery: {}\nLength recovery: {}\n",
+            self.scheme, self.sequence_number_base, self.timestamp_recovery, self.length_recovery
+        );
+        if let Some(ssrc) = self.protected_ssrc {
+            details.push_str(&format!("Protected SSRC: {:x}\n", ssrc));
+        }
+        details.push_str(&format!(
+            "Protected sequence numbers: {:?}\n",
+            self.protected_sequence_numbers
+        ));
+        details
+    }
+}
+
+impl fmt::Display for FecPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} base {} protects {} packets",
+            self.scheme,
+            self.sequence_number_base,
+            self.protected_sequence_numbers.len()
+        )
+    }
+}
+
+fn get_flexfec_recovery(header: &[u8]) -> (u16, u32) {
+    let length_recovery = u16::from_be_bytes([header[2], header[3]]);
+    let timestamp_recovery = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+    (length_recovery, timestamp_recovery)
+}
+
+// SN base followed by either the flexible mask or the L and D fields
+fn get_flexfec_protected(header: &[u8], protected: &[u8]) -> Option<(u16, Vec<u16>)> {
+    let is_fixed = header[0] & 0x40 != 0;
+    let sequence_number_base = u16::from_be_bytes([*protected.first()?, *protected.get(1)?]);
+
+    let protected_sequence_numbers = if is_fixed {
+        let &[columns, rows] = protected.get(2..4)? else {
+            return None;
+        };
+        fixed_sequence_numbers(sequence_number_base, columns, rows)
+    } else {
+        flexible_sequence_numbers(protected.get(2..)?, sequence_number_base)?
+    };
+
+    Some((sequence_number_base, protected_sequence_numbers))
+}
+
+fn mask_to_sequence_numbers(mask: &[u8], base: u16) -> Vec<u16> {
+    (0..mask.len() * 8)
+        .filter(|bit| mask[bit / 8] & (0x80 >> (bit % 8)) != 0)
+        .map(|bit| base.wrapping_add(bit as u16))
+        .collect()
+}
+
+// flexible mask consists of 15, 46 or 109 bits,
+// each part preceded by a k-bit that signals the last part
+fn flexible_sequence_numbers(mask: &[u8], base: u16) -> Option<Vec<u16>> {
+    let mut offsets = Vec::new();
+    let mut bit = 0;
+    let mut next_offset = 0;
+    for part_length in [15, 31, 63] {
+        let is_last = mask.get(bit / 8)? & (0x80 >> (bit % 8)) != 0;
+        bit += 1;
+        for _ in 0..part_length {
+            if mask.get(bit / 8)? & (0x80 >> (bit % 8)) != 0 {
+                offsets.push(next_offset);
+            }
+            bit += 1;
+            next_offset += 1;
+        }
+        if is_last {
+            break;
+        }
+    }
+
+    Some(
+        offsets
+            .into_iter()
+            .map(|offset| base.wrapping_add(offset))
+            .collect(),
+    )
+}
+
+fn fixed_sequence_numbers(base: u16, columns: u8, rows: u8) -> Vec<u16> {
+    match (columns, rows) {
+        // retransmission
+        (0, _) => vec![base],
+        // row FEC
+        (columns, 0 | 1) => (0..columns as u16)
+            .map(|offset| base.wrapping_add(offset))
+            .collect(),
+        // column FEC
+        (columns, rows) => (0..rows as u16)
+            .map(|row| base.wrapping_add(row * columns as u16))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ulpfec_mask() {
+        let payload = [
+            0x00, 0x60, 0x01, 0x00, 0x00, 0x00, 0x0b, 0xb8, 0x00, 0x64, // FEC header
+            0x00, 0x80, 0xa0, 0x01, // ULP level header, short mask
+        ];
+        let fec = FecPayload::build_ulpfec(&payload).unwrap();
+
+        assert_eq!(fec.sequence_number_base, 256);
+        assert_eq!(fec.protected_sequence_numbers, vec![256, 258, 271]);
+    }
+
+    #[test]
+    fn parses_flexfec_flexible_mask() {
+        let payload = [
+            0x00, 0x60, 0x00, 0x64, 0x00, 0x00, 0x0b, 0xb8, // FEC header
+            0x00, 0x0a, // SN base
+            0xc0, 0x01, // k-bit set, offsets 0 and 14
+        ];
+        let fec = FecPayload::build_flexfec(&payload).unwrap();
+
+        assert_eq!(fec.protected_ssrc, None);
+        assert_eq!(fec.protected_sequence_numbers, vec![10, 24]);
+    }
+
+    #[test]
+    fn parses_flexfec_03_flexible_mask() {
+        let payload = [
+            0x00, 0x60, 0x00, 0x64, 0x00, 0x00, 0x0b, 0xb8, // FEC header
+            0x01, 0x00, 0x00, 0x00, // SSRCCount, reserved
+            0x12, 0x34, 0x56, 0x78, 0x00, 0x0a, // SSRC, SN base
+            0xc0, 0x01, // k-bit set, offsets 0 and 14
+        ];
+        let fec = FecPayload::build_flexfec_03(&payload).unwrap();
+
+        assert_eq!(fec.protected_ssrc, Some(0x12345678));
+        assert_eq!(fec.protected_sequence_numbers, vec![10, 24]);
+    }
+}
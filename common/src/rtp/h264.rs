@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const STAP_A: u8 = 24;
+const FU_A: u8 = 28;
+
+const NON_IDR_SLICE: u8 = 1;
+const IDR_SLICE: u8 = 5;
+const SPS: u8 = 7;
+const PPS: u8 = 8;
+
+// RTP payload format for H.264, see RFC 6184
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum H264Packetization {
+    SingleNalUnit,
+    StapA,
+    FuA { start: bool, end: bool },
+    Other(u8),
+}
+
+impl fmt::Display for H264Packetization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SingleNalUnit => write!(f, "Single NAL"),
+            Self::StapA => write!(f, "STAP-A"),
+            Self::FuA {
+                start: true,
+                end: true,
+            } => write!(f, "FU-A (start, end)"),
+            Self::FuA { start: true, .. } => write!(f, "FU-A (start)"),
+            Self::FuA { end: true, .. } => write!(f, "FU-A (end)"),
+            Self::FuA { .. } => write!(f, "FU-A"),
+            Self::Other(nal_type) => write!(f, "{}", nal_type_name(*nal_type)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct H264NalUnit {
+    pub nal_ref_idc: u8,
+    pub nal_type: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct H264ProfileLevel {
+    pub profile_idc: u8,
+    pub constraint_flags: u8,
+    pub level_idc: u8,
+}
+
+impl H264ProfileLevel {
+    pub fn profile_name(&self) -> &'static str {
+        let constraint_set1 = self.constraint_flags & 0x40 != 0;
+
+        match self.profile_idc {
+            66 if constraint_set1 => "Constrained Baseline",
+            66 => "Baseline",
+            77 => "Main",
+            88 => "Extended",
+            100 => "High",
+            110 => "High 10",
+            122 => "High 4:2:2",
+            244 => "High 4:4:4",
+            _ => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for H264ProfileLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // level_idc is equal to ten times the level number
+        write!(
+            f,
+            "{} (profile-level-id {:02x}{:02x}{:02x}), level {:.1}",
+            self.profile_name(),
+            self.profile_idc,
+            self.constraint_flags,
+            self.level_idc,
+            self.level_idc as f64 / 10.0
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct H264Payload {
+    pub packetization: H264Packetization,
+    // for FU-A, only the fragmented NAL unit header is known
+    pub nal_units: Vec<H264NalUnit>,
+    pub profile_level: Option<H264ProfileLevel>,
+}
+
+impl H264Payload {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        let header = *payload.first()?;
+        let nal_type = header & 0x1f;
+        let nal_ref_idc = (header >> 5) & 0x03;
+
+        let mut profile_level = None;
+        let (packetization, nal_units) = match nal_type {
+            1..=23 => {
+                if nal_type == SPS {
+                    profile_level = parse_sps(&payload[1..]);
+                }
+                let nal_unit = H264NalUnit {
+                    nal_ref_idc,
+                    nal_type,
+                };
+                (H264Packetization::SingleNalUnit, vec![nal_unit])
+            }
+            STAP_A => {
+                let mut nal_units = Vec::new();
+                let mut rest = &payload[1..];
+                while rest.len() > 2 {
+                    let size = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+                    let nal = rest.get(2..2 + size)?;
+                    let nal_header = *nal.first()?;
+                    let nal_unit = H264NalUnit {
+                        nal_ref_idc: (nal_header >> 5) & 0x03,
+                        nal_type: nal_header & 0x1f,
+                    };
+                    if nal_unit.nal_type == SPS {
+                        profile_level = parse_sps(&nal[1..]);
+                    }
+                    nal_units.push(nal_unit);
+                    rest = &rest[2 + size..];
+                }
+                (H264Packetization::StapA, nal_units)
+            }
+            FU_A => {
+                let fu_header = *payload.get(1)?;
+                let packetization = H264Packetization::FuA {
+                    start: fu_header & 0x80 != 0,
+                    end: fu_header & 0x40 != 0,
+                };
+                let nal_unit = H264NalUnit {
+                    nal_ref_idc,
+                    nal_type: fu_header & 0x1f,
+                };
+                (packetization, vec![nal_unit])
+            }
+            _ => (H264Packetization::Other(nal_type), Vec::new()),
+        };
+
+        Some(Self {
+            packetization,
+            nal_units,
+            profile_level,
+        })
+    }
+
+    pub fn has_nal_type(&self, nal_type: u8) -> bool {
+        self.nal_units.iter().any(|nal| nal.nal_type == nal_type)
+    }
+
+    pub fn has_parameter_sets(&self) -> bool {
+        self.has_nal_type(SPS) || self.has_nal_type(PPS)
+    }
+
+    pub fn is_keyframe(&self) -> bool {
+        // mark only the first packet of a fragmented IDR slice
+        match self.packetization {
+            H264Packetization::FuA { start, .. } => start && self.has_nal_type(IDR_SLICE),
+            _ => self.has_nal_type(IDR_SLICE),
+        }
+    }
+
+    pub fn get_details(&self) -> String {
+        let mut details = format!("Packetization: {}\n", self.packetization);
+        for nal in &self.nal_units {
+            details.push_str(&format!(
+                "NAL unit: {} (type {}, NRI {})\n",
+                nal_type_name(nal.nal_type),
+                nal.nal_type,
+                nal.nal_ref_idc
+            ));
+        }
+        if let Some(profile_level) = self.profile_level {
+            details.push_str(&format!("Profile: {}\n", profile_level));
+        }
+        details
+    }
+}
+
+impl fmt::Display for H264Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nal_types = self
+            .nal_units
+            .iter()
+            .map(|nal| nal_type_name(nal.nal_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match self.packetization {
+            H264Packetization::SingleNalUnit => write!(f, "H264 {}", nal_types),
+            _ => write!(f, "H264 {} [{}]", self.packetization, nal_types),
+        }
+    }
+}
+
+fn parse_sps(sps: &[u8]) -> Option<H264ProfileLevel> {
+    let &[profile_idc, constraint_flags, level_idc, ..] = sps else {
+        return None;
+    };
+
+    Some(H264ProfileLevel {
+        profile_idc,
+        constraint_flags,
+        level_idc,
+    })
+}
+
+pub fn nal_type_name(nal_type: u8) -> &'static str {
+    match nal_type {
+        0 => "Unspecified",
+        NON_IDR_SLICE => "Non-IDR slice",
+        2..=4 => "Slice data partition",
+        IDR_SLICE => "IDR slice",
+        6 => "SEI",
+        SPS => "SPS",
+        PPS => "PPS",
+        9 => "Access unit delimiter",
+        10 => "End of sequence",
+        11 => "End of stream",
+        12 => "Filler data",
+        13..=23 => "Reserved",
+        STAP_A => "STAP-A",
+        25 => "STAP-B",
+        26 => "MTAP16",
+        27 => "MTAP24",
+        FU_A => "FU-A",
+        29 => "FU-B",
+        _ => "Unspecified",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stap_a_with_parameter_sets() {
+        let payload = [
+            0x78, // STAP-A, NRI 3
+            0x00, 0x04, 0x67, 0x42, 0xc0, 0x1f, // SPS, Constrained Baseline 3.1
+            0x00, 0x02, 0x68, 0xce, // PPS
+        ];
+        let h264 = H264Payload::build(&payload).unwrap();
+
+        assert!(matches!(h264.packetization, H264Packetization::StapA));
+        assert_eq!(h264.nal_units.len(), 2);
+        assert!(h264.has_parameter_sets());
+        assert!(!h264.is_keyframe());
+
+        let profile_level = h264.profile_level.unwrap();
+        assert_eq!(profile_level.profile_name(), "Constrained Baseline");
+        assert_eq!(profile_level.level_idc, 31);
+    }
+
+    #[test]
+    fn parses_fu_a_idr_fragments() {
+        let start = H264Payload::build(&[0x7c, 0x85, 0xb8]).unwrap();
+        let middle = H264Payload::build(&[0x7c, 0x05, 0x11]).unwrap();
+        let end = H264Payload::build(&[0x7c, 0x45, 0x22]).unwrap();
+
+        assert!(start.is_keyframe());
+        assert!(!middle.is_keyframe());
+        assert!(!end.is_keyframe());
+        assert!(matches!(
+            end.packetization,
+            H264Packetization::FuA {
+                start: false,
+                end: true
+            }
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_stap_a() {
+        assert!(H264Payload::build(&[0x78, 0x00, 0x05, 0x67]).is_none());
+    }
+}
@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const AGGREGATION_PACKET: u8 = 48;
+const FRAGMENTATION_UNIT: u8 = 49;
+const PACI: u8 = 50;
+
+const VPS: u8 = 32;
+const SPS: u8 = 33;
+const PPS: u8 = 34;
+
+// RTP payload format for H.265/HEVC, see RFC 7798
+// assumes that DONL fields are not present (sprop-max-don-diff equal to 0)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum H265Packetization {
+    SingleNalUnit,
+    AggregationPacket,
+    FragmentationUnit { start: bool, end: bool },
+    Paci,
+    Other(u8),
+}
+
+impl fmt::Display for H265Packetization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SingleNalUnit => write!(f, "Single NAL"),
+            Self::AggregationPacket => write!(f, "AP"),
+            Self::FragmentationUnit {
+                start: true,
+                end: true,
+            } => write!(f, "FU (start, end)"),
+            Self::FragmentationUnit { start: true, .. } => write!(f, "FU (start)"),
+            Self::FragmentationUnit { end: true, .. } => write!(f, "FU (end)"),
+            Self::FragmentationUnit { .. } => write!(f, "FU"),
+            Self::Paci => write!(f, "PACI"),
+            Self::Other(nal_type) => write!(f, "{}", nal_type_name(*nal_type)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct H265NalUnit {
+    pub nal_type: u8,
+    pub layer_id: u8,
+    pub temporal_id: u8,
+}
+
+impl H265NalUnit {
+    fn new(header: [u8; 2]) -> Self {
+        Self {
+            nal_type: (header[0] >> 1) & 0x3f,
+            layer_id: ((header[0] & 0x01) << 5) | (header[1] >> 3),
+            // TID is transmitted with an offset of 1
+            temporal_id: (header[1] & 0x07).saturating_sub(1),
+        }
+    }
+
+    pub fn is_irap(&self) -> bool {
+        matches!(self.nal_type, 16..=23)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct H265Payload {
+    pub packetization: H265Packetization,
+    // for FU, only the fragmented NAL unit header is known
+    pub nal_units: Vec<H265NalUnit>,
+}
+
+impl H265Payload {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        let header = [*payload.first()?, *payload.get(1)?];
+        let payload_header = H265NalUnit::new(header);
+
+        let (packetization, nal_units) = match payload_header.nal_type {
+            0..=47 => (H265Packetization::SingleNalUnit, vec![payload_header]),
+            AGGREGATION_PACKET => {
+                let mut nal_units = Vec::new();
+                let mut rest = &payload[2..];
+                while rest.len() > 2 {
+                    let size = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+                    let nal = rest.get(2..2 + size)?;
+                    let nal_header = [*nal.first()?, *nal.get(1)?];
+                    nal_units.push(H265NalUnit::new(nal_header));
+                    rest = &rest[2 + size..];
+                }
+                (H265Packetization::AggregationPacket, nal_units)
+            }
+            FRAGMENTATION_UNIT => {
+                let fu_header = *payload.get(2)?;
+                let packetization = H265Packetization::FragmentationUnit {
+                    start: fu_header & 0x80 != 0,
+                    end: fu_header & 0x40 != 0,
+                };
+                let nal_unit = H265NalUnit {
+                    nal_type: fu_header & 0x3f,
+                    ..payload_header
+                };
+                (packetization, vec![nal_unit])
+            }
+            PACI => (H265Packetization::Paci, Vec::new()),
+            nal_type => (H265Packetization::Other(nal_type), Vec::new()),
+        };
+
+        Some(Self {
+            packetization,
+            nal_units,
+        })
+    }
+
+    pub fn has_nal_type(&self, nal_type: u8) -> bool {
+        self.nal_units.iter().any(|nal| nal.nal_type == nal_type)
+    }
+
+    pub fn has_parameter_sets(&self) -> bool {
+        self.has_nal_type(VPS) || self.has_nal_type(SPS) || self.has_nal_type(PPS)
+    }
+
+    pub fn is_keyframe(&self) -> bool {
+        let has_irap = self.nal_units.iter().any(H265NalUnit::is_irap);
+
+        // mark only the first packet of a fragmented IRAP picture
+        match self.packetization {
+            H265Packetization::FragmentationUnit { start, .. } => start && has_irap,
+            _ => has_irap,
+        }
+    }
+
+    pub fn get_details(&self) -> String {
+        let mut details = format!("Packetization: {}\n", self.packetization);
+        for nal in &self.nal_units {
+            details.push_str(&format!(
+                "NAL unit: {} (type {}, layer {}, TID {})\n",
+                nal_type_name(nal.nal_type),
+                nal.nal_type,
+                nal.layer_id,
+                nal.temporal_id
+            ));
+        }
+        details
+    }
+}
+
+impl fmt::Display for H265Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nal_types = self
+            .nal_units
+            .iter()
+            .map(|nal| nal_type_name(nal.nal_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match self.packetization {
+            H265Packetization::SingleNalUnit => write!(f, "H265 {}", nal_types),
+            _ => write!(f, "H265 {} [{}]", self.packetization, nal_types),
+        }
+    }
+}
+
+pub fn nal_type_name(nal_type: u8) -> &'static str {
+    match nal_type {
+        0 | 1 => "TRAIL",
+        2 | 3 => "TSA",
+        4 | 5 => "STSA",
+        6 | 7 => "RADL",
+        8 | 9 => "RASL",
+        10..=15 => "Reserved non-IRAP",
+        16..=18 => "BLA",
+        19 | 20 => "IDR",
+        21 => "CRA",
+        22 | 23 => "Reserved IRAP",
+        24..=31 => "Reserved",
+        VPS => "VPS",
+        SPS => "SPS",
+        PPS => "PPS",
+        35 => "Access unit delimiter",
+        36 => "End of sequence",
+        37 => "End of bitstream",
+        38 => "Filler data",
+        39 => "Prefix SEI",
+        40 => "Suffix SEI",
+        41..=47 => "Reserved",
+        AGGREGATION_PACKET => "AP",
+        FRAGMENTATION_UNIT => "FU",
+        PACI => "PACI",
+        _ => "Unspecified",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_aggregation_packet_with_parameter_sets() {
+        let payload = [
+            0x60, 0x01, // AP
+            0x00, 0x03, 0x40, 0x01, 0x0c, // VPS
+            0x00, 0x03, 0x42, 0x01, 0x01, // SPS
+            0x00, 0x03, 0x44, 0x01, 0xc1, // PPS
+        ];
+        let h265 = H265Payload::build(&payload).unwrap();
+
+        assert!(matches!(
+            h265.packetization,
+            H265Packetization::AggregationPacket
+        ));
+        assert_eq!(h265.nal_units.len(), 3);
+        assert!(h265.has_parameter_sets());
+        assert!(!h265.is_keyframe());
+    }
+
+    #[test]
+    fn parses_fragmented_idr() {
+        let start = H265Payload::build(&[0x62, 0x01, 0x93, 0xaf]).unwrap();
+        let end = H265Payload::build(&[0x62, 0x01, 0x53, 0xaf]).unwrap();
+
+        assert!(start.is_keyframe());
+        assert!(!end.is_keyframe());
+        assert_eq!(start.nal_units[0].nal_type, 19);
+    }
+}
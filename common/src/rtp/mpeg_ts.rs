@@ -0,0 +1,310 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub const TS_PACKET_LENGTH: usize = 188;
+pub const PAT_PID: u16 = 0x0000;
+pub const NULL_PID: u16 = 0x1fff;
+pub const PCR_CLOCK_RATE: f64 = 27_000_000.0;
+// 33-bit base in 90 kHz units times 300 plus the 9-bit extension
+pub const PCR_MODULUS: u64 = (1 << 33) * 300;
+
+const SYNC_BYTE: u8 = 0x47;
+const PMT_TABLE_ID: u8 = 0x02;
+
+// MPEG-2 transport stream packets carried in RTP, see RFC 2250, section 2
+// and ISO/IEC 13818-1, section 2.4.3
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MpegTsPayload {
+    pub packets: Vec<TsPacket>,
+    // programs from PAT, if the payload carries it
+    pub programs: Option<Vec<Program>>,
+    pub pmts: Vec<Pmt>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TsPacket {
+    pub pid: u16,
+    // other fields are not parsed if the sync byte is invalid
+    pub is_sync_byte_valid: bool,
+    pub transport_error: bool,
+    pub payload_unit_start: bool,
+    pub has_payload: bool,
+    pub continuity_counter: u8,
+    pub discontinuity: bool,
+    pub pcr: Option<u64>, // in 27 MHz units
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Program {
+    pub program_number: u16,
+    pub pmt_pid: u16,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pmt {
+    pub pid: u16,
+    pub program_number: u16,
+    pub pcr_pid: u16,
+    // stream type and PID of elementary streams
+    pub streams: Vec<(u8, u16)>,
+}
+
+impl MpegTsPayload {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        if payload.len() < TS_PACKET_LENGTH {
+            return None;
+        }
+
+        let mut packets = Vec::new();
+        let mut programs = None;
+        let mut pmts = Vec::new();
+        let (chunks, _) = payload.as_chunks::<TS_PACKET_LENGTH>();
+        for data in chunks {
+            let (packet, section) = parse_packet(data);
+            // only sections that fit into a single TS packet are considered,
+            // which is typically the case for PAT and PMT
+            if let Some(section) = section {
+                if packet.pid == PAT_PID {
+                    programs = parse_pat(section).or(programs);
+                } else if let Some(pmt) = parse_pmt(packet.pid, section) {
+                    pmts.push(pmt);
+                }
+            }
+            packets.push(packet);
+        }
+
+        Some(Self {
+            packets,
+            programs,
+            pmts,
+        })
+    }
+
+    pub fn get_details(&self) -> String {
+        let mut details = format!("TS packets: {}\n", self.packets.len());
+        for packet in &self.packets {
+            if !packet.is_sync_byte_valid {
+                details.push_str("Invalid sync byte\n");
+                continue;
+            }
+            details.push_str(&format!(
+                "PID 0x{:04x}, CC {}",
+                packet.pid, packet.continuity_counter
+            ));
+            if let Some(pcr) = packet.pcr {
+                details.push_str(&format!(", PCR {:.6} s", pcr as f64 / PCR_CLOCK_RATE));
+            }
+            if packet.discontinuity {
+                details.push_str(", discontinuity");
+            }
+            details.push('\n');
+        }
+        if let Some(programs) = &self.programs {
+            for program in programs {
+                details.push_str(&format!(
+                    "PAT: program {} PMT PID 0x{:04x}\n",
+                    program.program_number, program.pmt_pid
+                ));
+            }
+        }
+        for pmt in &self.pmts {
+            details.push_str(&format!(
+                "PMT: program {} PCR PID 0x{:04x}\n",
+                pmt.program_number, pmt.pcr_pid
+            ));
+            for (stream_type, pid) in &pmt.streams {
+                details.push_str(&format!(
+                    "    {} PID 0x{:04x}\n",
+                    stream_type_name(*stream_type),
+                    pid
+                ));
+            }
+        }
+        details
+    }
+}
+
+impl fmt::Display for MpegTsPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MP2T {} packets", self.packets.len())?;
+        if self.programs.is_some() {
+            write!(f, " PAT")?;
+        }
+        if !self.pmts.is_empty() {
+            write!(f, " PMT")?;
+        }
+        if self.packets.iter().any(|packet| packet.pcr.is_some()) {
+            write!(f, " PCR")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn stream_type_name(stream_type: u8) -> String {
+    let name = match stream_type {
+        0x01 => "MPEG-1 video",
+        0x02 => "MPEG-2 video",
+        0x03 => "MPEG-1 audio",
+        0x04 => "MPEG-2 audio",
+        0x06 => "PES private data",
+        0x0f => "AAC audio",
+        0x11 => "LATM AAC audio",
+        0x1b => "H.264 video",
+        0x24 => "H.265 video",
+        0x81 => "AC-3 audio",
+        _ => return format!("type 0x{:02x}", stream_type),
+    };
+    name.to_string()
+}
+
+// returns the packet and the section starting in it, if there's one
+fn parse_packet(data: &[u8]) -> (TsPacket, Option<&[u8]>) {
+    let mut packet = TsPacket {
+        pid: u16::from_be_bytes([data[1] & 0x1f, data[2]]),
+        is_sync_byte_valid: data[0] == SYNC_BYTE,
+        transport_error: data[1] & 0x80 != 0,
+        payload_unit_start: data[1] & 0x40 != 0,
+        has_payload: data[3] & 0x10 != 0,
+        continuity_counter: data[3] & 0x0f,
+        discontinuity: false,
+        pcr: None,
+    };
+    if !packet.is_sync_byte_valid {
+        return (packet, None);
+    }
+
+    let mut payload_offset = 4;
+    if data[3] & 0x20 != 0 {
+        let length = data[4] as usize;
+        if length > 0 {
+            let flags = data[5];
+            packet.discontinuity = flags & 0x80 != 0;
+            if flags & 0x10 != 0 && length >= 7 {
+                packet.pcr = Some(parse_pcr(&data[6..12]));
+            }
+        }
+        payload_offset += 1 + length;
+    }
+
+    if !packet.has_payload || !packet.payload_unit_start || payload_offset >= data.len() {
+        return (packet, None);
+    }
+
+    let pointer = data[payload_offset] as usize;
+    let section = data.get(payload_offset + 1 + pointer..);
+    (packet, section)
+}
+
+// 33-bit base in 90 kHz units, 6 reserved bits and 9-bit extension
+fn parse_pcr(data: &[u8]) -> u64 {
+    let base = (data[0] as u64) << 25
+        | (data[1] as u64) << 17
+        | (data[2] as u64) << 9
+        | (data[3] as u64) << 1
+        | (data[4] as u64) >> 7;
+    let extension = ((data[4] & 0x01) as u64) << 8 | data[5] as u64;
+    base * 300 + extension
+}
+
+// returns table id and the section's content between the common header and CRC
+fn parse_section(section: &[u8]) -> Option<(u8, &[u8])> {
+    let table_id = *section.first()?;
+    let length = (u16::from_be_bytes([*section.get(1)?, *section.get(2)?]) & 0x0fff) as usize;
+    // 5 bytes of extended header (id, version, section numbers) and 4 bytes of CRC
+    let content = section.get(8..3 + length.checked_sub(4)?)?;
+    Some((table_id, content))
+}
+
+fn parse_pat(section: &[u8]) -> Option<Vec<Program>> {
+    let (0x00, content) = parse_section(section)? else {
+        return None;
+    };
+
+    let (entries, _) = content.as_chunks::<4>();
+    let programs = entries
+        .iter()
+        .map(|entry| Program {
+            program_number: u16::from_be_bytes([entry[0], entry[1]]),
+            pmt_pid: u16::from_be_bytes([entry[2] & 0x1f, entry[3]]),
+        })
+        // program 0 points to the network information table
+        .filter(|program| program.program_number != 0)
+        .collect();
+
+    Some(programs)
+}
+
+fn parse_pmt(pid: u16, section: &[u8]) -> Option<Pmt> {
+    let (PMT_TABLE_ID, content) = parse_section(section)? else {
+        return None;
+    };
+    let program_number = u16::from_be_bytes([*section.get(3)?, *section.get(4)?]);
+    let pcr_pid = u16::from_be_bytes([*content.first()? & 0x1f, *content.get(1)?]);
+    let info_length = (u16::from_be_bytes([*content.get(2)?, *content.get(3)?]) & 0x0fff) as usize;
+
+    let mut streams = Vec::new();
+    let mut position = 4 + info_length;
+    while let Some(entry) = content.get(position..position + 5) {
+        let pid = u16::from_be_bytes([entry[1] & 0x1f, entry[2]]);
+        let es_info_length = (u16::from_be_bytes([entry[3], entry[4]]) & 0x0fff) as usize;
+        streams.push((entry[0], pid));
+        position += 5 + es_info_length;
+    }
+
+    Some(Pmt {
+        pid,
+        program_number,
+        pcr_pid,
+        streams,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_packet(header: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut data = header.to_vec();
+        data.extend_from_slice(payload);
+        data.resize(TS_PACKET_LENGTH, 0xff);
+        data
+    }
+
+    #[test]
+    fn parses_pat_pmt_and_pcr() {
+        let mut payload = build_packet(
+            &[0x47, 0x40, 0x00, 0x10, 0x00],
+            // PAT: program 1 with PMT PID 0x1000
+            &[
+                0x00, 0xb0, 0x0d, 0x00, 0x01, 0xc1, 0x00, 0x00, 0x00, 0x01, 0xf0, 0x00, 0x00, 0x00,
+                0x00, 0x00,
+            ],
+        );
+        payload.extend(build_packet(
+            &[0x47, 0x50, 0x00, 0x11, 0x00],
+            // PMT: PCR PID 0x100, H.264 video on PID 0x100
+            &[
+                0x02, 0xb0, 0x12, 0x00, 0x01, 0xc1, 0x00, 0x00, 0xe1, 0x00, 0xf0, 0x00, 0x1b, 0xe1,
+                0x00, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+        ));
+        // adaptation field with PCR of 1 s and no payload
+        payload.extend(build_packet(
+            &[0x47, 0x01, 0x00, 0x25, 0x07, 0x10],
+            &[0x00, 0x00, 0xaf, 0xc8, 0x00, 0x00],
+        ));
+        let ts = MpegTsPayload::build(&payload).unwrap();
+
+        assert_eq!(ts.packets.len(), 3);
+        let programs = ts.programs.unwrap();
+        assert_eq!(programs[0].program_number, 1);
+        assert_eq!(programs[0].pmt_pid, 0x1000);
+        assert_eq!(ts.pmts[0].pid, 0x1000);
+        assert_eq!(ts.pmts[0].pcr_pid, 0x100);
+        assert_eq!(ts.pmts[0].streams, vec![(0x1b, 0x100)]);
+        assert_eq!(ts.packets[2].pid, 0x100);
+        assert_eq!(ts.packets[2].continuity_counter, 5);
+        assert!(!ts.packets[2].has_payload);
+        assert_eq!(ts.packets[2].pcr, Some(27_000_000));
+    }
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// DTX packets carry only the TOC byte (and possibly a single byte of data)
+const MAX_DTX_PAYLOAD_LENGTH: usize = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum OpusMode {
+    Silk,
+    Hybrid,
+    Celt,
+}
+
+impl fmt::Display for OpusMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            Self::Silk => "SILK",
+            Self::Hybrid => "Hybrid",
+            Self::Celt => "CELT",
+        };
+
+        write!(f, "{}", res)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum OpusBandwidth {
+    Narrowband,
+    Mediumband,
+    Wideband,
+    SuperWideband,
+    Fullband,
+}
+
+impl fmt::Display for OpusBandwidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            Self::Narrowband => "NB",
+            Self::Mediumband => "MB",
+            Self::Wideband => "WB",
+            Self::SuperWideband => "SWB",
+            Self::Fullband => "FB",
+        };
+
+        write!(f, "{}", res)
+    }
+}
+
+// Opus TOC byte, see RFC 6716, section 3.1
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpusPayload {
+    pub config: u8,
+    pub mode: OpusMode,
+    pub bandwidth: OpusBandwidth,
+    pub frame_duration: f64, // in ms
+    pub frame_count: u8,
+    pub stereo: bool,
+    pub is_dtx: bool,
+    pub has_fec: bool,
+}
+
+impl OpusPayload {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        use OpusBandwidth::*;
+        use OpusMode::*;
+
+        let toc = *payload.first()?;
+        let config = toc >> 3;
+        let stereo = toc & 0x04 != 0;
+
+        let (mode, bandwidth, frame_durations) = match config {
+            0..=3 => (Silk, Narrowband, [10.0, 20.0, 40.0, 60.0]),
+            4..=7 => (Silk, Mediumband, [10.0, 20.0, 40.0, 60.0]),
+            8..=11 => (Silk, Wideband, [10.0, 20.0, 40.0, 60.0]),
+            12 | 13 => (Hybrid, SuperWideband, [10.0, 20.0, 10.0, 20.0]),
+            14 | 15 => (Hybrid, Fullband, [10.0, 20.0, 10.0, 20.0]),
+            16..=19 => (Celt, Narrowband, [2.5, 5.0, 10.0, 20.0]),
+            20..=23 => (Celt, Wideband, [2.5, 5.0, 10.0, 20.0]),
+            24..=27 => (Celt, SuperWideband, [2.5, 5.0, 10.0, 20.0]),
+            _ => (Celt, Fullband, [2.5, 5.0, 10.0, 20.0]),
+        };
+        let frame_duration = frame_durations[(config % 4) as usize];
+
+        let frame_count = match toc & 0x03 {
+            0 => 1,
+            1 | 2 => 2,
+            _ => *payload.get(1)? & 0x3f,
+        };
+
+        let is_dtx = payload.len() <= MAX_DTX_PAYLOAD_LENGTH;
+        let has_fec = !is_dtx && mode != Celt && has_lbrr(payload, frame_duration, stereo);
+
+        Some(Self {
+            config,
+            mode,
+            bandwidth,
+            frame_duration,
+            frame_count,
+            stereo,
+            is_dtx,
+            has_fec,
+        })
+    }
+
+    pub fn get_packet_duration(&self) -> f64 {
+        self.frame_duration * self.frame_count as f64
+    }
+
+    pub fn get_details(&self) -> String {
+        format!(
+            "Config: {}\nMode: {}\nBandwidth: {}\nFrame size: {} ms\nFrame count: {}\n\
+             Stereo: {}\nDTX: {}\nFEC (LBRR): {}\n",
+            self.config,
+            self.mode,
+            self.bandwidth,
+            self.frame_duration,
+            self.frame_count,
+            self.stereo,
+            self.is_dtx,
+            self.has_fec
+        )
+    }
+}
+
+impl fmt::Display for OpusPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_dtx {
+            return write!(f, "Opus DTX");
+        }
+
+        write!(
+            f,
+            "Opus {} {} {}x{} ms{}",
+            self.mode,
+            self.bandwidth,
+            self.frame_count,
+            self.frame_duration,
+            if self.stereo { " stereo" } else { "" }
+        )?;
+        if self.has_fec {
+            write!(f, " FEC")?;
+        }
+
+        Ok(())
+    }
+}
+
+// LBRR flags are the first bits of the SILK layer of the first frame,
+// preceded by a VAD flag for every SILK frame (20 ms) in the Opus frame
+fn has_lbrr(payload: &[u8], frame_duration: f64, stereo: bool) -> bool {
+    let Some(frame) = get_first_frame(payload) else {
+        return false;
+    };
+    let Some(first_byte) = frame.first() else {
+        return false;
+    };
+
+    let silk_frames = match frame_duration as u32 {
+        40 => 2,
+        60 => 3,
+        _ => 1,
+    };
+    let channels = if stereo { 2 } else { 1 };
+
+    (0..channels).any(|channel| {
+        let bit = (channel + 1) * (silk_frames + 1) - 1;
+        first_byte & (0x80 >> bit) != 0
+    })
+}
+
+fn get_first_frame(payload: &[u8]) -> Option<&[u8]> {
+    let offset = match payload.first()? & 0x03 {
+        0 | 1 => 1,
+        2 => match *payload.get(1)? {
+            0..=251 => 2,
+            _ => 3,
+        },
+        _ => {
+            let frame_count_byte = *payload.get(1)?;
+            let is_vbr = frame_count_byte & 0x80 != 0;
+            let has_padding = frame_count_byte & 0x40 != 0;
+            let frame_count = (frame_count_byte & 0x3f) as usize;
+
+            let mut offset = 2;
+            if has_padding {
+                while *payload.get(offset)? == 255 {
+                    offset += 1;
+                }
+                offset += 1;
+            }
+            if is_vbr {
+                for _ in 1..frame_count {
+                    offset += match *payload.get(offset)? {
+                        0..=251 => 1,
+                        _ => 2,
+                    };
+                }
+            }
+            offset
+        }
+    };
+
+    payload.get(offset..)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_toc() {
+        // config 9 (SILK WB 20 ms), mono, code 0, LBRR flag set
+        let opus = OpusPayload::build(&[0x48, 0x40, 0x12, 0x34]).unwrap();
+
+        assert_eq!(opus.mode, OpusMode::Silk);
+        assert_eq!(opus.bandwidth, OpusBandwidth::Wideband);
+        assert_eq!(opus.get_packet_duration(), 20.0);
+        assert!(!opus.stereo);
+        assert!(!opus.is_dtx);
+        assert!(opus.has_fec);
+    }
+
+    #[test]
+    fn detects_dtx() {
+        // config 31 (CELT FB 20 ms), stereo, code 3 with 3 frames
+        let opus = OpusPayload::build(&[0xff, 0x03]).unwrap();
+
+        assert_eq!(opus.mode, OpusMode::Celt);
+        assert_eq!(opus.frame_count, 3);
+        assert!(opus.stereo);
+        assert!(opus.is_dtx);
+    }
+}
@@ -0,0 +1,149 @@
+use super::av1::Av1Payload;
+use super::comfort_noise::ComfortNoise;
+use super::fec::FecPayload;
+use super::h264::H264Payload;
+use super::h265::H265Payload;
+use super::mpeg_ts::MpegTsPayload;
+use super::opus::OpusPayload;
+use super::payload_type::PayloadType;
+use super::red::RedPayload;
+use super::rtx::RtxPayload;
+use super::telephone_event::TelephoneEvent;
+use super::vp8::Vp8Payload;
+use super::vp9::Vp9Payload;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// information decoded from the RTP payload,
+// requires the encoding name to be known (e.g. from SDP)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum PayloadInfo {
+    H264(H264Payload),
+    H265(H265Payload),
+    Vp8(Vp8Payload),
+    Vp9(Vp9Payload),
+    Av1(Av1Payload),
+    Opus(OpusPayload),
+    TelephoneEvent(TelephoneEvent),
+    ComfortNoise(ComfortNoise),
+    Red(RedPayload),
+    Fec(FecPayload),
+    Rtx(RtxPayload),
+    MpegTs(MpegTsPayload),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Layer {
+    pub spatial_id: u8,
+    pub temporal_id: u8,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "S{}T{}", self.spatial_id, self.temporal_id)
+    }
+}
+
+impl PayloadInfo {
+    pub fn build(payload_type: &PayloadType, payload: &[u8]) -> Option<Self> {
+        match payload_type.name.to_uppercase().as_str() {
+            "H264" => H264Payload::build(payload).map(Self::H264),
+            "H265" => H265Payload::build(payload).map(Self::H265),
+            "VP8" => Vp8Payload::build(payload).map(Self::Vp8),
+            "VP9" => Vp9Payload::build(payload).map(Self::Vp9),
+            "AV1" => Av1Payload::build(payload).map(Self::Av1),
+            "OPUS" => OpusPayload::build(payload).map(Self::Opus),
+            "TELEPHONE-EVENT" => TelephoneEvent::build(payload).map(Self::TelephoneEvent),
+            "CN" => ComfortNoise::build(payload).map(Self::ComfortNoise),
+            "RED" => RedPayload::build(payload).map(Self::Red),
+            "ULPFEC" => FecPayload::build_ulpfec(payload).map(Self::Fec),
+            "FLEXFEC" => FecPayload::build_flexfec(payload).map(Self::Fec),
+            "FLEXFEC-03" => FecPayload::build_flexfec_03(payload).map(Self::Fec),
+            "RTX" => RtxPayload::build(payload).map(Self::Rtx),
+            "MP2T" => MpegTsPayload::build(payload).map(Self::MpegTs),
+            _ => None,
+        }
+    }
+
+    pub fn is_keyframe(&self) -> bool {
+        match self {
+            Self::H264(h264) => h264.is_keyframe(),
+            Self::H265(h265) => h265.is_keyframe(),
+            Self::Vp8(vp8) => vp8.is_keyframe,
+            Self::Vp9(vp9) => vp9.is_keyframe(),
+            Self::Av1(av1) => av1.is_keyframe(),
+            Self::Red(red) => red.primary.as_ref().is_some_and(|p| p.is_keyframe()),
+            Self::Opus(_)
+            | Self::TelephoneEvent(_)
+            | Self::ComfortNoise(_)
+            | Self::Fec(_)
+            | Self::Rtx(_)
+            | Self::MpegTs(_) => false,
+        }
+    }
+
+    // payload carried in the primary RED block or the payload itself
+    pub fn get_primary(&self) -> &Self {
+        match self {
+            Self::Red(RedPayload {
+                primary: Some(primary),
+                ..
+            }) => primary,
+            _ => self,
+        }
+    }
+
+    pub fn is_fec(&self) -> bool {
+        matches!(self.get_primary(), Self::Fec(_))
+    }
+
+    pub fn get_layer(&self) -> Option<Layer> {
+        match self.get_primary() {
+            Self::Vp8(vp8) => vp8.temporal_id.map(|temporal_id| Layer {
+                spatial_id: 0,
+                temporal_id,
+            }),
+            Self::Vp9(vp9) => Some(Layer {
+                spatial_id: vp9.spatial_id?,
+                temporal_id: vp9.temporal_id?,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn get_details(&self) -> String {
+        match self {
+            Self::H264(h264) => h264.get_details(),
+            Self::H265(h265) => h265.get_details(),
+            Self::Vp8(vp8) => vp8.get_details(),
+            Self::Vp9(vp9) => vp9.get_details(),
+            Self::Av1(av1) => av1.get_details(),
+            Self::Opus(opus) => opus.get_details(),
+            Self::TelephoneEvent(event) => event.get_details(),
+            Self::ComfortNoise(cn) => cn.get_details(),
+            Self::Red(red) => red.get_details(),
+            Self::Fec(fec) => fec.get_details(),
+            Self::Rtx(rtx) => rtx.get_details(),
+            Self::MpegTs(ts) => ts.get_details(),
+        }
+    }
+}
+
+impl fmt::Display for PayloadInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::H264(h264) => write!(f, "{}", h264),
+            Self::H265(h265) => write!(f, "{}", h265),
+            Self::Vp8(vp8) => write!(f, "{}", vp8),
+            Self::Vp9(vp9) => write!(f, "{}", vp9),
+            Self::Av1(av1) => write!(f, "{}", av1),
+            Self::Opus(opus) => write!(f, "{}", opus),
+            Self::TelephoneEvent(event) => write!(f, "{}", event),
+            Self::ComfortNoise(cn) => write!(f, "{}", cn),
+            Self::Red(red) => write!(f, "{}", red),
+            Self::Fec(fec) => write!(f, "{}", fec),
+            Self::Rtx(rtx) => write!(f, "{}", rtx),
+            Self::MpegTs(ts) => write!(f, "{}", ts),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use super::payload::PayloadInfo;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// RTP payload for redundant audio data, see RFC 2198
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RedPayload {
+    // redundant blocks first, primary block last
+    pub blocks: Vec<RedBlock>,
+    // decoded primary block, set by the caller as it requires
+    // the payload type mapping of the block's payload type
+    pub primary: Option<Box<PayloadInfo>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct RedBlock {
+    pub payload_type: u8,
+    pub timestamp_offset: Option<u16>, // None for the primary block
+    pub offset: usize,                 // offset of block data in the RED payload
+    pub length: usize,
+}
+
+impl RedPayload {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        let mut headers = Vec::new();
+        let mut position = 0;
+        loop {
+            let first = *payload.get(position)?;
+            let payload_type = first & 0x7f;
+            if first & 0x80 == 0 {
+                position += 1;
+                headers.push((payload_type, None, None));
+                break;
+            }
+
+            let &[_, offset_high, offset_low_length_high, length_low] =
+                payload.get(position..position + 4)?
+            else {
+                return None;
+            };
+            let timestamp_offset = (offset_high as u16) << 6 | (offset_low_length_high as u16) >> 2;
+            let length = ((offset_low_length_high & 0x03) as usize) << 8 | length_low as usize;
+            headers.push((payload_type, Some(timestamp_offset), Some(length)));
+            position += 4;
+        }
+
+        let mut blocks = Vec::new();
+        for (payload_type, timestamp_offset, length) in headers {
+            // primary block takes the rest of the payload
+            let length = length.unwrap_or(payload.len().saturating_sub(position));
+            if position + length > payload.len() {
+                return None;
+            }
+            blocks.push(RedBlock {
+                payload_type,
+                timestamp_offset,
+                offset: position,
+                length,
+            });
+            position += length;
+        }
+
+        Some(Self {
+            blocks,
+            primary: None,
+        })
+    }
+
+    pub fn get_primary_block(&self) -> &RedBlock {
+        // there's always at least the primary block
+        self.blocks.last().unwrap()
+    }
+
+    pub fn get_redundancy(&self) -> usize {
+        self.blocks.len() - 1
+    }
+
+    pub fn get_details(&self) -> String {
+        let mut details = String::new();
+        for block in &self.blocks {
+            match block.timestamp_offset {
+                Some(offset) => details.push_str(&format!(
+                    "Redundant block: PT {}, timestamp offset {}, {} bytes\n",
+                    block.payload_type, offset, block.length
+                )),
+                None => details.push_str(&format!(
+                    "Primary block: PT {}, {} bytes\n",
+                    block.payload_type, block.length
+                )),
+            }
+        }
+        if let Some(primary) = &self.primary {
+            details.push_str(&primary.get_details());
+        }
+        details
+    }
+}
+
+impl fmt::Display for RedPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RED")?;
+        if self.get_redundancy() > 0 {
+            write!(f, " +{} redundant", self.get_redundancy())?;
+        }
+        match &self.primary {
+            Some(primary) => write!(f, " [{}]", primary),
+            None => write!(f, " [PT {}]", self.get_primary_block().payload_type),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_redundant_and_primary_blocks() {
+        // redundant PT 111 with offset 960 and 2 bytes, primary PT 111
+        let payload = [0xef, 0x0f, 0x00, 0x02, 0x6f, 0xaa, 0xbb, 0xcc, 0xdd, 0xee];
+        let red = RedPayload::build(&payload).unwrap();
+
+        assert_eq!(red.get_redundancy(), 1);
+        assert_eq!(red.blocks[0].timestamp_offset, Some(960));
+        assert_eq!(red.blocks[0].offset, 5);
+        assert_eq!(red.blocks[0].length, 2);
+        let primary = red.get_primary_block();
+        assert_eq!(primary.payload_type, 111);
+        assert_eq!(primary.offset, 7);
+        assert_eq!(primary.length, 3);
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// retransmission payload, see RFC 4588, section 4
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RtxPayload {
+    pub original_sequence_number: u16,
+    pub original_payload_length: usize,
+}
+
+impl RtxPayload {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        let &[osn_high, osn_low, ..] = payload else {
+            return None;
+        };
+
+        Some(Self {
+            original_sequence_number: u16::from_be_bytes([osn_high, osn_low]),
+            original_payload_length: payload.len() - 2,
+        })
+    }
+
+    pub fn get_details(&self) -> String {
+        format!(
+            "Original sequence number: {}\nOriginal payload length: {}\n",
+            self.original_sequence_number, self.original_payload_length
+        )
+    }
+}
+
+impl fmt::Display for RtxPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RTX OSN {}", self.original_sequence_number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_original_sequence_number() {
+        let rtx = RtxPayload::build(&[0x12, 0x34, 0x90, 0x00]).unwrap();
+
+        assert_eq!(rtx.original_sequence_number, 0x1234);
+        assert_eq!(rtx.original_payload_length, 2);
+    }
+}
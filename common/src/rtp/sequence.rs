@@ -0,0 +1,126 @@
+// extended sequence number calculation, see RFC 3550, Appendix A.1,
+// without the probation (streams are already confirmed before, see `FlowClassifier`)
+const MAX_DROPOUT: u16 = 3000;
+const MAX_MISORDER: u16 = 100;
+const SEQUENCE_NUMBER_MOD: u32 = 1 << 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SequenceUpdate {
+    // newest packet, possibly after a gap
+    InOrder,
+    // duplicate or reordered packet
+    OutOfOrder,
+    // large jump, not accounted until confirmed by the next packet
+    Jump,
+    // sequence numbering restarted after a confirmed jump
+    Restart,
+}
+
+#[derive(Debug)]
+pub struct SequenceTracker {
+    max_sequence_number: u16,
+    // shifted by a single cycle, so that packets reordered
+    // before the first one still get a valid extended sequence number
+    cycles: u32,
+    bad_sequence_number: Option<u16>,
+}
+
+impl SequenceTracker {
+    pub fn new(sequence_number: u16) -> Self {
+        Self {
+            max_sequence_number: sequence_number,
+            cycles: SEQUENCE_NUMBER_MOD,
+            bad_sequence_number: None,
+        }
+    }
+
+    pub fn get_extended_max(&self) -> u32 {
+        self.cycles + self.max_sequence_number as u32
+    }
+
+    // returns extended sequence number of the packet
+    pub fn update(&mut self, sequence_number: u16) -> (u32, SequenceUpdate) {
+        let delta = sequence_number.wrapping_sub(self.max_sequence_number);
+
+        if delta == 0 {
+            return (self.get_extended_max(), SequenceUpdate::OutOfOrder);
+        }
+
+        if delta < MAX_DROPOUT {
+            if sequence_number < self.max_sequence_number {
+                self.cycles += SEQUENCE_NUMBER_MOD;
+            }
+            self.max_sequence_number = sequence_number;
+            self.bad_sequence_number = None;
+            return (self.get_extended_max(), SequenceUpdate::InOrder);
+        }
+
+        if delta <= (SEQUENCE_NUMBER_MOD - MAX_MISORDER as u32) as u16 {
+            // two sequential packets after the jump mean that the source restarted
+            if self.bad_sequence_number == Some(sequence_number) {
+                self.max_sequence_number = sequence_number;
+                self.bad_sequence_number = None;
+                return (self.get_extended_max(), SequenceUpdate::Restart);
+            }
+            self.bad_sequence_number = Some(sequence_number.wrapping_add(1));
+            return (self.cycles + sequence_number as u32, SequenceUpdate::Jump);
+        }
+
+        // packet from before the last wraparound
+        let cycles = if sequence_number > self.max_sequence_number {
+            self.cycles - SEQUENCE_NUMBER_MOD
+        } else {
+            self.cycles
+        };
+        (cycles + sequence_number as u32, SequenceUpdate::OutOfOrder)
+    }
+}
+
+// extended sequence number closest to the reference one, for sequence numbers
+// carried in the payload of another packet, e.g. by RTX or FEC
+pub fn extend_sequence_number(sequence_number: u16, reference: u32) -> u32 {
+    let delta = sequence_number.wrapping_sub(reference as u16) as i16;
+    reference.wrapping_add_signed(delta as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extends_across_wraparound() {
+        let mut tracker = SequenceTracker::new(65534);
+        let first = tracker.get_extended_max();
+
+        assert_eq!(tracker.update(0), (first + 2, SequenceUpdate::InOrder));
+        // reordered from before the wraparound
+        assert_eq!(
+            tracker.update(65535),
+            (first + 1, SequenceUpdate::OutOfOrder)
+        );
+        assert_eq!(tracker.update(0), (first + 2, SequenceUpdate::OutOfOrder));
+        assert_eq!(tracker.update(5), (first + 7, SequenceUpdate::InOrder));
+    }
+
+    #[test]
+    fn restarts_after_confirmed_jump() {
+        let mut tracker = SequenceTracker::new(100);
+
+        assert_eq!(tracker.update(20000).1, SequenceUpdate::Jump);
+        assert_eq!(tracker.update(101).1, SequenceUpdate::InOrder);
+        assert_eq!(tracker.update(30000).1, SequenceUpdate::Jump);
+        let (extended, update) = tracker.update(30001);
+
+        assert_eq!(update, SequenceUpdate::Restart);
+        assert_eq!(extended, tracker.get_extended_max());
+        assert_eq!(tracker.update(30002).1, SequenceUpdate::InOrder);
+    }
+
+    #[test]
+    fn extends_relative_to_reference() {
+        let reference = SEQUENCE_NUMBER_MOD * 2 + 65530;
+
+        assert_eq!(extend_sequence_number(65520, reference), reference - 10);
+        assert_eq!(extend_sequence_number(4, reference), reference + 10);
+    }
+}
@@ -0,0 +1,15 @@
+// SDES items carried in RTP header extensions, used to identify streams
+// sharing a transport (BUNDLE) and simulcast layers, see RFC 8843 and RFC 8852
+pub const MID_URI: &str = "urn:ietf:params:rtp-hdrext:sdes:mid";
+pub const RID_URI: &str = "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id";
+pub const REPAIRED_RID_URI: &str = "urn:ietf:params:rtp-hdrext:sdes:repaired-rtp-stream-id";
+
+pub fn parse_sdes_value(data: &[u8]) -> Option<String> {
+    // value might be padded with zeros
+    let value = std::str::from_utf8(data).ok()?.trim_end_matches('\0');
+    if value.is_empty() {
+        return None;
+    }
+
+    Some(value.to_string())
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// DTMF named telephone event, see RFC 4733, section 2.3
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TelephoneEvent {
+    pub event: u8,
+    pub end: bool,
+    pub volume: u8,
+    pub duration: u16, // in RTP timestamp units
+}
+
+impl TelephoneEvent {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        let &[event, flags, duration_high, duration_low, ..] = payload else {
+            return None;
+        };
+
+        Some(Self {
+            event,
+            end: flags & 0x80 != 0,
+            volume: flags & 0x3f,
+            duration: u16::from_be_bytes([duration_high, duration_low]),
+        })
+    }
+
+    pub fn get_details(&self) -> String {
+        format!(
+            "Event: {} ({})\nEnd: {}\nVolume: -{} dBm0\nDuration: {} RTP timestamp units\n",
+            event_name(self.event),
+            self.event,
+            self.end,
+            self.volume,
+            self.duration
+        )
+    }
+}
+
+impl fmt::Display for TelephoneEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DTMF {}", event_name(self.event))?;
+        if self.end {
+            write!(f, " (end)")?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn event_name(event: u8) -> String {
+    match event {
+        0..=9 => event.to_string(),
+        10 => "*".to_string(),
+        11 => "#".to_string(),
+        12..=15 => ((b'A' + event - 12) as char).to_string(),
+        16 => "Flash".to_string(),
+        _ => format!("Event {}", event),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_end_packet() {
+        // event #, end, volume 10, duration 800
+        let event = TelephoneEvent::build(&[0x0b, 0x8a, 0x03, 0x20]).unwrap();
+
+        assert_eq!(event_name(event.event), "#");
+        assert!(event.end);
+        assert_eq!(event.volume, 10);
+        assert_eq!(event.duration, 800);
+    }
+}
@@ -0,0 +1,12 @@
+// transport-wide sequence number, shared by all of the streams sent over the same transport,
+// see https://datatracker.ietf.org/doc/html/draft-holmer-rmcat-transport-wide-cc-extensions-01
+pub const TRANSPORT_CC_URI: &str =
+    "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01";
+
+pub fn parse_transport_sequence_number(data: &[u8]) -> Option<u16> {
+    let &[high, low] = data else {
+        return None;
+    };
+
+    Some(u16::from_be_bytes([high, low]))
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// VP8 payload descriptor, see RFC 7741
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Vp8Payload {
+    pub non_reference: bool,
+    pub start_of_partition: bool,
+    pub partition_index: u8,
+    pub picture_id: Option<u16>,
+    pub tl0_pic_idx: Option<u8>,
+    pub temporal_id: Option<u8>,
+    pub layer_sync: bool,
+    pub key_index: Option<u8>,
+    // can only be determined from the first packet of a frame
+    pub is_keyframe: bool,
+}
+
+impl Vp8Payload {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        let mut bytes = payload.iter();
+
+        let first = *bytes.next()?;
+        let extended = first & 0x80 != 0;
+        let non_reference = first & 0x20 != 0;
+        let start_of_partition = first & 0x10 != 0;
+        let partition_index = first & 0x07;
+
+        let mut picture_id = None;
+        let mut tl0_pic_idx = None;
+        let mut temporal_id = None;
+        let mut layer_sync = false;
+        let mut key_index = None;
+
+        if extended {
+            let control = *bytes.next()?;
+
+            if control & 0x80 != 0 {
+                let id = *bytes.next()?;
+                picture_id = if id & 0x80 != 0 {
+                    let low = *bytes.next()?;
+                    Some(u16::from_be_bytes([id & 0x7f, low]))
+                } else {
+                    Some(id as u16)
+                };
+            }
+            if control & 0x40 != 0 {
+                tl0_pic_idx = Some(*bytes.next()?);
+            }
+            let has_tid = control & 0x20 != 0;
+            let has_key_index = control & 0x10 != 0;
+            if has_tid || has_key_index {
+                let byte = *bytes.next()?;
+                if has_tid {
+                    temporal_id = Some(byte >> 6);
+                    layer_sync = byte & 0x20 != 0;
+                }
+                if has_key_index {
+                    key_index = Some(byte & 0x1f);
+                }
+            }
+        }
+
+        // the inverse key frame flag is the first bit of the VP8 payload header
+        let is_keyframe = match bytes.next() {
+            Some(header) if start_of_partition && partition_index == 0 => header & 0x01 == 0,
+            _ => false,
+        };
+
+        Some(Self {
+            non_reference,
+            start_of_partition,
+            partition_index,
+            picture_id,
+            tl0_pic_idx,
+            temporal_id,
+            layer_sync,
+            key_index,
+            is_keyframe,
+        })
+    }
+
+    pub fn is_frame_start(&self) -> bool {
+        self.start_of_partition && self.partition_index == 0
+    }
+
+    pub fn get_details(&self) -> String {
+        let mut details = format!(
+            "Start of partition: {}\nPartition index: {}\nNon-reference frame: {}\n",
+            self.start_of_partition, self.partition_index, self.non_reference
+        );
+        if let Some(picture_id) = self.picture_id {
+            details.push_str(&format!("Picture ID: {}\n", picture_id));
+        }
+        if let Some(tl0_pic_idx) = self.tl0_pic_idx {
+            details.push_str(&format!("TL0PICIDX: {}\n", tl0_pic_idx));
+        }
+        if let Some(temporal_id) = self.temporal_id {
+            details.push_str(&format!(
+                "Temporal layer: {} (layer sync: {})\n",
+                temporal_id, self.layer_sync
+            ));
+        }
+        if let Some(key_index) = self.key_index {
+            details.push_str(&format!("KEYIDX: {}\n", key_index));
+        }
+        details.push_str(&format!("Keyframe: {}\n", self.is_keyframe));
+        details
+    }
+}
+
+impl fmt::Display for Vp8Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VP8")?;
+        if let Some(picture_id) = self.picture_id {
+            write!(f, " pic {}", picture_id)?;
+        }
+        if let Some(temporal_id) = self.temporal_id {
+            write!(f, " T{}", temporal_id)?;
+        }
+        if self.is_frame_start() {
+            write!(f, " (start)")?;
+        }
+        if self.is_keyframe {
+            write!(f, " keyframe")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_extended_descriptor() {
+        // X, S, PID 0; I, L, T; 15 bit picture ID; TL0PICIDX; TID 2, Y
+        let payload = [0x90, 0xe0, 0x81, 0x23, 0x05, 0xa0, 0x10];
+        let vp8 = Vp8Payload::build(&payload).unwrap();
+
+        assert_eq!(vp8.picture_id, Some(0x0123));
+        assert_eq!(vp8.tl0_pic_idx, Some(5));
+        assert_eq!(vp8.temporal_id, Some(2));
+        assert!(vp8.layer_sync);
+        assert!(vp8.is_frame_start());
+        assert!(vp8.is_keyframe);
+    }
+}
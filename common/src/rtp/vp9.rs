@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// VP9 payload descriptor, see RFC 9628
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Vp9Payload {
+    pub inter_picture_predicted: bool,
+    pub flexible_mode: bool,
+    pub start_of_frame: bool,
+    pub end_of_frame: bool,
+    pub scalability_structure: bool,
+    pub not_reference: bool,
+    pub picture_id: Option<u16>,
+    pub temporal_id: Option<u8>,
+    pub switching_up: bool,
+    pub spatial_id: Option<u8>,
+    pub inter_layer_dependency: bool,
+    pub tl0_pic_idx: Option<u8>,
+    pub reference_indices: Vec<u8>,
+}
+
+impl Vp9Payload {
+    pub fn build(payload: &[u8]) -> Option<Self> {
+        let mut bytes = payload.iter();
+
+        let first = *bytes.next()?;
+        let has_picture_id = first & 0x80 != 0;
+        let inter_picture_predicted = first & 0x40 != 0;
+        let has_layer_indices = first & 0x20 != 0;
+        let flexible_mode = first & 0x10 != 0;
+
+        let mut picture_id = None;
+        if has_picture_id {
+            let id = *bytes.next()?;
+            picture_id = if id & 0x80 != 0 {
+                let low = *bytes.next()?;
+                Some(u16::from_be_bytes([id & 0x7f, low]))
+            } else {
+                Some(id as u16)
+            };
+        }
+
+        let mut temporal_id = None;
+        let mut switching_up = false;
+        let mut spatial_id = None;
+        let mut inter_layer_dependency = false;
+        let mut tl0_pic_idx = None;
+        if has_layer_indices {
+            let layers = *bytes.next()?;
+            temporal_id = Some(layers >> 5);
+            switching_up = layers & 0x10 != 0;
+            spatial_id = Some((layers >> 1) & 0x07);
+            inter_layer_dependency = layers & 0x01 != 0;
+
+            if !flexible_mode {
+                tl0_pic_idx = Some(*bytes.next()?);
+            }
+        }
+
+        let mut reference_indices = Vec::new();
+        if flexible_mode && inter_picture_predicted {
+            // up to 3 reference indices, N bit signals that another one follows
+            loop {
+                let reference = *bytes.next()?;
+                reference_indices.push(reference >> 1);
+                if reference & 0x01 == 0 || reference_indices.len() == 3 {
+                    break;
+                }
+            }
+        }
+
+        Some(Self {
+            inter_picture_predicted,
+            flexible_mode,
+            start_of_frame: first & 0x08 != 0,
+            end_of_frame: first & 0x04 != 0,
+            scalability_structure: first & 0x02 != 0,
+            not_reference: first & 0x01 != 0,
+            picture_id,
+            temporal_id,
+            switching_up,
+            spatial_id,
+            inter_layer_dependency,
+            tl0_pic_idx,
+            reference_indices,
+        })
+    }
+
+    pub fn is_keyframe(&self) -> bool {
+        self.start_of_frame && !self.inter_picture_predicted && self.spatial_id.unwrap_or(0) == 0
+    }
+
+    pub fn get_details(&self) -> String {
+        let mut details = format!(
+            "Start of frame: {}\nEnd of frame: {}\nInter-picture predicted: {}\n\
+             Flexible mode: {}\nScalability structure present: {}\nNot a reference: {}\n",
+            self.start_of_frame,
+            self.end_of_frame,
+            self.inter_picture_predicted,
+            self.flexible_mode,
+            self.scalability_structure,
+            self.not_reference
+        );
+        if let Some(picture_id) = self.picture_id {
+            details.push_str(&format!("Picture ID: {}\n", picture_id));
+        }
+        if let (Some(spatial_id), Some(temporal_id)) = (self.spatial_id, self.temporal_id) {
+            details.push_str(&format!(
+                "Spatial layer: {} (inter-layer dependency: {})\n",
+                spatial_id, self.inter_layer_dependency
+            ));
+            details.push_str(&format!(
+                "Temporal layer: {} (switching up point: {})\n",
+                temporal_id, self.switching_up
+            ));
+        }
+        if let Some(tl0_pic_idx) = self.tl0_pic_idx {
+            details.push_str(&format!("TL0PICIDX: {}\n", tl0_pic_idx));
+        }
+        if !self.reference_indices.is_empty() {
+            details.push_str(&format!("P_DIFF: {:?}\n", self.reference_indices));
+        }
+        details
+    }
+}
+
+impl fmt::Display for Vp9Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VP9")?;
+        if let Some(picture_id) = self.picture_id {
+            write!(f, " pic {}", picture_id)?;
+        }
+        if let (Some(spatial_id), Some(temporal_id)) = (self.spatial_id, self.temporal_id) {
+            write!(f, " S{}T{}", spatial_id, temporal_id)?;
+        }
+        match (self.start_of_frame, self.end_of_frame) {
+            (true, true) => write!(f, " (start, end)")?,
+            (true, false) => write!(f, " (start)")?,
+            (false, true) => write!(f, " (end)")?,
+            _ => {}
+        };
+        if self.is_keyframe() {
+            write!(f, " keyframe")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_non_flexible_descriptor() {
+        // I, L, B, V; 7 bit picture ID; TID 1, SID 0; TL0PICIDX
+        let payload = [0xaa, 0x11, 0x20, 0x07];
+        let vp9 = Vp9Payload::build(&payload).unwrap();
+
+        assert_eq!(vp9.picture_id, Some(0x11));
+        assert_eq!(vp9.temporal_id, Some(1));
+        assert_eq!(vp9.spatial_id, Some(0));
+        assert_eq!(vp9.tl0_pic_idx, Some(7));
+        assert!(vp9.scalability_structure);
+        assert!(vp9.is_keyframe());
+    }
+
+    #[test]
+    fn parses_flexible_mode_references() {
+        // I, P, L, F, E; 7 bit picture ID; TID 0, SID 1; two P_DIFFs
+        let payload = [0xf4, 0x12, 0x02, 0x03, 0x04];
+        let vp9 = Vp9Payload::build(&payload).unwrap();
+
+        assert_eq!(vp9.spatial_id, Some(1));
+        assert_eq!(vp9.tl0_pic_idx, None);
+        assert_eq!(vp9.reference_indices, vec![1, 2]);
+        assert!(!vp9.is_keyframe());
+    }
+}
@@ -2,9 +2,61 @@ use crate::rtp::payload_type::PayloadType;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Sdp {
     pub payload_types: HashMap<u8, PayloadType>,
+    // RTP header extension id to its URI
+    pub extensions: HashMap<u8, String>,
+    // RTX payload type to the associated payload type (`apt`)
+    pub rtx_payload_types: HashMap<u8, u8>,
+    // SSRCs declared with `a=ssrc`
+    pub ssrcs: Vec<u32>,
+    // primary and RTX SSRC pairs declared with `a=ssrc-group:FID`
+    pub fid_groups: Vec<(u32, u32)>,
+    // protected and FEC SSRC pairs declared with `a=ssrc-group:FEC-FR`
+    pub fec_fr_groups: Vec<(u32, u32)>,
+    pub mid: Option<String>,
+    // simulcast RIDs in the order of `a=simulcast` (or `a=rid` if missing)
+    pub rids: Vec<String>,
+}
+
+impl Sdp {
+    pub fn get_extension_id(&self, uri: &str) -> Option<u8> {
+        self.extensions
+            .iter()
+            .find(|(_, ext_uri)| *ext_uri == uri)
+            .map(|(id, _)| *id)
+    }
+
+    pub fn get_fid_primary_ssrc(&self, rtx_ssrc: u32) -> Option<u32> {
+        self.fid_groups
+            .iter()
+            .find(|(_, ssrc)| *ssrc == rtx_ssrc)
+            .map(|(primary_ssrc, _)| *primary_ssrc)
+    }
+
+    pub fn get_fec_fr_protected_ssrc(&self, fec_ssrc: u32) -> Option<u32> {
+        self.fec_fr_groups
+            .iter()
+            .find(|(_, ssrc)| *ssrc == fec_ssrc)
+            .map(|(protected_ssrc, _)| *protected_ssrc)
+    }
+
+    pub fn get_rid_index(&self, rid: &str) -> Option<usize> {
+        self.rids.iter().position(|sdp_rid| sdp_rid == rid)
+    }
+
+    pub fn declares_ssrc(&self, ssrc: u32) -> bool {
+        self.ssrcs.contains(&ssrc)
+            || self
+                .fid_groups
+                .iter()
+                .any(|(primary_ssrc, rtx_ssrc)| *primary_ssrc == ssrc || *rtx_ssrc == ssrc)
+            || self
+                .fec_fr_groups
+                .iter()
+                .any(|(protected_ssrc, fec_ssrc)| *protected_ssrc == ssrc || *fec_ssrc == ssrc)
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -19,9 +71,7 @@ impl Sdp {
         let mut lines = raw_sdp.lines();
 
         // first line should be media
-        let Some(first_line) = lines.next() else {
-            return None;
-        };
+        let first_line = lines.next()?;
         let Ok(SdpLine {
             sdp_type: SdpType::Media(media),
             ..
@@ -38,27 +88,163 @@ impl Sdp {
             }
         };
 
-        let payload_types = lines
-            .filter_map(|line| {
-                let Ok(SdpLine {
-                    sdp_type: SdpType::Attribute(SdpAttribute::Rtpmap(rtpmap)),
-                    ..
-                }) = parse_sdp_line(line, 1)
-                else {
-                    return None;
-                };
-
-                let pt = PayloadType {
-                    id: rtpmap.payload_type,
-                    name: rtpmap.codec_name,
-                    clock_rate: Some(rtpmap.frequency),
-                    media_type,
-                };
-
-                Some((pt.id, pt))
-            })
+        let mut payload_types = HashMap::new();
+        let mut extensions = HashMap::new();
+        let mut rtx_payload_types = HashMap::new();
+        let mut ssrcs = Vec::new();
+        let mut fid_groups = Vec::new();
+        let mut fec_fr_groups = Vec::new();
+        let mut mid = None;
+        let mut rids = Vec::new();
+        let mut simulcast_rids = Vec::new();
+
+        for line in lines {
+            if let Some((id, uri)) = parse_extmap(line) {
+                extensions.insert(id, uri);
+                continue;
+            }
+            if let Some((id, apt)) = parse_apt(line) {
+                rtx_payload_types.insert(id, apt);
+                continue;
+            }
+            if let Some(ssrc) = parse_ssrc(line) {
+                if !ssrcs.contains(&ssrc) {
+                    ssrcs.push(ssrc);
+                }
+                continue;
+            }
+            if let Some(group) = parse_ssrc_group(line, "FID") {
+                fid_groups.push(group);
+                continue;
+            }
+            if let Some(group) = parse_ssrc_group(line, "FEC-FR") {
+                fec_fr_groups.push(group);
+                continue;
+            }
+            if let Some(value) = line.trim().strip_prefix("a=mid:") {
+                mid = Some(value.to_string());
+                continue;
+            }
+            if let Some(rid) = parse_rid(line) {
+                rids.push(rid);
+                continue;
+            }
+            if let Some(send_rids) = parse_simulcast(line) {
+                simulcast_rids = send_rids;
+                continue;
+            }
+
+            let Ok(SdpLine {
+                sdp_type: SdpType::Attribute(SdpAttribute::Rtpmap(rtpmap)),
+                ..
+            }) = parse_sdp_line(line, 1)
+            else {
+                continue;
+            };
+
+            let pt = PayloadType {
+                id: rtpmap.payload_type,
+                name: rtpmap.codec_name,
+                clock_rate: Some(rtpmap.frequency),
+                media_type,
+            };
+
+            payload_types.insert(pt.id, pt);
+        }
+
+        Some(Self {
+            payload_types,
+            extensions,
+            rtx_payload_types,
+            ssrcs,
+            fid_groups,
+            fec_fr_groups,
+            mid,
+            rids: if simulcast_rids.is_empty() {
+                rids
+            } else {
+                simulcast_rids
+            },
+        })
+    }
+}
+
+// a=extmap:<id>[/<direction>] <URI> [<extension attributes>], see RFC 8285
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_extmap(line: &str) -> Option<(u8, String)> {
+    let value = line.trim().strip_prefix("a=extmap:")?;
+    let mut words = value.split_whitespace();
+
+    let id = words.next()?.split('/').next()?.parse().ok()?;
+    let uri = words.next()?.to_string();
+
+    Some((id, uri))
+}
+
+// a=fmtp:<payload type> apt=<associated payload type>[;...], see RFC 4588, section 8.6
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_apt(line: &str) -> Option<(u8, u8)> {
+    let value = line.trim().strip_prefix("a=fmtp:")?;
+    let (id, parameters) = value.split_once(' ')?;
+
+    let apt = parameters
+        .split(';')
+        .find_map(|parameter| parameter.trim().strip_prefix("apt="))?;
+
+    Some((id.parse().ok()?, apt.parse().ok()?))
+}
+
+// a=ssrc:<ssrc> <attribute>[:<value>], see RFC 5576
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_ssrc(line: &str) -> Option<u32> {
+    let value = line.trim().strip_prefix("a=ssrc:")?;
+    value.split_whitespace().next()?.parse().ok()
+}
+
+// a=ssrc-group:FID <primary ssrc> <rtx ssrc>, see RFC 5576,
+// or a=ssrc-group:FEC-FR <protected ssrc> <fec ssrc>, see RFC 5956
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_ssrc_group(line: &str, semantics: &str) -> Option<(u32, u32)> {
+    let value = line.trim().strip_prefix("a=ssrc-group:")?;
+    let mut words = value.split_whitespace();
+    if words.next()? != semantics {
+        return None;
+    }
+
+    let primary_ssrc = words.next()?.parse().ok()?;
+    let secondary_ssrc = words.next()?.parse().ok()?;
+
+    Some((primary_ssrc, secondary_ssrc))
+}
+
+// a=rid:<rid> <direction> [<restrictions>], see RFC 8851
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_rid(line: &str) -> Option<String> {
+    let value = line.trim().strip_prefix("a=rid:")?;
+    value.split_whitespace().next().map(String::from)
+}
+
+// a=simulcast:send <rid>[,<alternative>];<rid> [recv ...], see RFC 8853,
+// only the first alternative of each stream is taken, `~` marks paused streams
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_simulcast(line: &str) -> Option<Vec<String>> {
+    let value = line.trim().strip_prefix("a=simulcast:")?;
+    let mut words = value.split_whitespace();
+
+    // SDP of the receiving side describes the streams with `recv`
+    let mut rids = None;
+    while let (Some(direction), Some(streams)) = (words.next(), words.next()) {
+        let direction_rids = streams
+            .split(';')
+            .filter_map(|stream| stream.split(',').next())
+            .map(|rid| rid.trim_start_matches('~').to_string())
             .collect();
 
-        Some(Self { payload_types })
+        if direction == "send" {
+            return Some(direction_rids);
+        }
+        rids.get_or_insert(direction_rids);
     }
+
+    rids
 }
@@ -1,5 +1,6 @@
 use crate::server;
 use crate::sniffer::{Error, Sniffer};
+use rtpeeker_common::QualityRule;
 use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
@@ -23,10 +24,17 @@ pub struct Run {
     /// Port used by the application
     #[arg(short, long, default_value_t = DEFAULT_PORT)]
     port: u16,
+    /// File with quality rules, one per line, e.g. "jitter > 30 ms" or "loss > 2% over 5 s"
+    #[arg(short, long)]
+    rules: Option<String>,
 }
 
 impl Run {
     pub async fn run(self) {
+        let Some(quality_rules) = read_quality_rules(&self.rules) else {
+            return;
+        };
+
         let live_filter = self.create_capture_filter();
 
         let mut file_sniffers = get_sniffers(self.files, Sniffer::from_file);
@@ -52,7 +60,7 @@ impl Run {
         }
 
         let address = SocketAddr::new(self.address, self.port);
-        server::run(sniffers, address).await;
+        server::run(sniffers, address, quality_rules).await;
     }
 
     fn create_capture_filter(&self) -> String {
@@ -92,8 +100,33 @@ where
         .collect()
 }
 
+fn read_quality_rules(path: &Option<String>) -> Option<Vec<QualityRule>> {
+    let Some(path) = path else {
+        return Some(Vec::new());
+    };
+
+    let rules_str = match std::fs::read_to_string(path) {
+        Ok(rules_str) => rules_str,
+        Err(err) => {
+            println!("Error: failed to read rules file {}, reason: {}", path, err);
+            return None;
+        }
+    };
+
+    match QualityRule::parse_rules(&rules_str) {
+        Ok(rules) => Some(rules),
+        Err(line) => {
+            println!(
+                "Error: invalid rule in line {} of rules file {}",
+                line, path
+            );
+            None
+        }
+    }
+}
+
 fn apply_filters(sniffers: &mut HashMap<String, Sniffer>, filter: &str) -> Result<(), Error> {
-    for (_, sniffer) in sniffers.iter_mut() {
+    for sniffer in sniffers.values_mut() {
         if let err @ Err(_) = sniffer.apply_filter(filter) {
             return err;
         }
@@ -4,8 +4,10 @@ use futures_util::{
     SinkExt, StreamExt, TryFutureExt,
 };
 use log::{error, info, warn};
+use rtpeeker_common::decode_rule::RuleTarget;
+use rtpeeker_common::flow::FlowClassifier;
 use rtpeeker_common::packet::SessionProtocol;
-use rtpeeker_common::{Request, Response, Sdp};
+use rtpeeker_common::{DecodeRule, QualityRule, Request, Response, Sdp};
 use rtpeeker_common::{Source, StreamKey};
 use rust_embed::RustEmbed;
 use std::collections::HashMap;
@@ -14,7 +16,7 @@ use std::sync::{
     atomic::{AtomicUsize, Ordering},
     Arc,
 };
-use tokio::sync::{mpsc, mpsc::UnboundedSender, RwLock};
+use tokio::sync::{mpsc, mpsc::UnboundedSender, Mutex, RwLock};
 use warp::ws::{Message, WebSocket};
 use warp::{http::header::HeaderValue, path::Tail, reply};
 use warp::{Filter, Rejection, Reply};
@@ -43,33 +45,83 @@ impl Client {
 type Clients = Arc<RwLock<HashMap<usize, Client>>>;
 type Packets = Arc<RwLock<Vec<Response>>>;
 type PacketsMap = Arc<HashMap<Source, Packets>>;
-
-pub async fn run(sniffers: HashMap<String, Sniffer>, addr: SocketAddr) {
+type Sdps = Arc<RwLock<HashMap<StreamKey, Sdp>>>;
+type SdpsMap = Arc<HashMap<Source, Sdps>>;
+type DecodeRules = Arc<RwLock<Vec<DecodeRule>>>;
+type DecodeRulesMap = Arc<HashMap<Source, DecodeRules>>;
+// classifier of the live capture, replaced when the packets are reclassified
+type Classifier = Arc<Mutex<FlowClassifier>>;
+type ClassifiersMap = Arc<HashMap<Source, Classifier>>;
+type QualityRules = Arc<RwLock<Vec<QualityRule>>>;
+
+pub async fn run(
+    sniffers: HashMap<String, Sniffer>,
+    addr: SocketAddr,
+    quality_rules: Vec<QualityRule>,
+) {
     let clients = Clients::default();
+    let quality_rules = Arc::new(RwLock::new(quality_rules));
     let mut source_to_packets = HashMap::new();
+    let mut source_to_sdps = HashMap::new();
+    let mut source_to_rules = HashMap::new();
+    let mut source_to_classifiers = HashMap::new();
 
     // a bit of repetition, but Rust bested me this time
     for (_file, sniffer) in sniffers {
         let packets = Packets::default();
+        let rules = DecodeRules::default();
+        let classifier = Classifier::default();
         source_to_packets.insert(sniffer.source.clone(), packets.clone());
+        source_to_sdps.insert(sniffer.source.clone(), Sdps::default());
+        source_to_rules.insert(sniffer.source.clone(), rules.clone());
+        source_to_classifiers.insert(sniffer.source.clone(), classifier.clone());
 
         let cloned_clients = clients.clone();
         tokio::task::spawn(async move {
-            sniff(sniffer, packets, cloned_clients).await;
+            sniff(sniffer, packets, rules, classifier, cloned_clients).await;
         });
     }
 
     let source_to_packets = Arc::new(source_to_packets);
+    let source_to_sdps = Arc::new(source_to_sdps);
+    let source_to_rules = Arc::new(source_to_rules);
+    let source_to_classifiers = Arc::new(source_to_classifiers);
 
     let clients_filter = warp::any().map(move || clients.clone());
     let source_to_packets_filter = warp::any().map(move || source_to_packets.clone());
+    let source_to_sdps_filter = warp::any().map(move || source_to_sdps.clone());
+    let source_to_rules_filter = warp::any().map(move || source_to_rules.clone());
+    let source_to_classifiers_filter = warp::any().map(move || source_to_classifiers.clone());
+    let quality_rules_filter = warp::any().map(move || quality_rules.clone());
     let ws = warp::path(WS_PATH)
         .and(warp::ws())
         .and(clients_filter)
         .and(source_to_packets_filter)
-        .map(|ws: warp::ws::Ws, clients_cl, source_to_packets_cl| {
-            ws.on_upgrade(move |socket| client_connected(socket, clients_cl, source_to_packets_cl))
-        });
+        .and(source_to_sdps_filter)
+        .and(source_to_rules_filter)
+        .and(source_to_classifiers_filter)
+        .and(quality_rules_filter)
+        .map(
+            |ws: warp::ws::Ws,
+             clients_cl,
+             source_to_packets_cl,
+             source_to_sdps_cl,
+             source_to_rules_cl,
+             source_to_classifiers_cl,
+             quality_rules_cl| {
+                ws.on_upgrade(move |socket| {
+                    client_connected(
+                        socket,
+                        clients_cl,
+                        source_to_packets_cl,
+                        source_to_sdps_cl,
+                        source_to_rules_cl,
+                        source_to_classifiers_cl,
+                        quality_rules_cl,
+                    )
+                })
+            },
+        );
 
     let index_html = warp::path::end().and_then(serve_index);
     let other = warp::path::tail().and_then(serve);
@@ -100,7 +152,15 @@ async fn serve_impl(path: &str) -> Result<impl Reply, Rejection> {
     Ok(res)
 }
 
-async fn client_connected(ws: WebSocket, clients: Clients, source_to_packets: PacketsMap) {
+async fn client_connected(
+    ws: WebSocket,
+    clients: Clients,
+    source_to_packets: PacketsMap,
+    source_to_sdps: SdpsMap,
+    source_to_rules: DecodeRulesMap,
+    source_to_classifiers: ClassifiersMap,
+    quality_rules: QualityRules,
+) {
     let client_id = NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
 
     info!("New client connected, assigned id: {}", client_id);
@@ -124,7 +184,17 @@ async fn client_connected(ws: WebSocket, clients: Clients, source_to_packets: Pa
 
     clients.write().await.insert(client_id, Client::new(tx));
 
-    handle_messages(client_id, ws_rx, &clients, &source_to_packets).await;
+    handle_messages(
+        client_id,
+        ws_rx,
+        &clients,
+        &source_to_packets,
+        &source_to_sdps,
+        &source_to_rules,
+        &source_to_classifiers,
+        &quality_rules,
+    )
+    .await;
 
     info!("Client disconnected, client_id: {}", client_id);
     clients.write().await.remove(&client_id);
@@ -152,11 +222,26 @@ async fn send_pcap_filenames(
         .await;
 }
 
-async fn sniff(mut sniffer: Sniffer, packets: Packets, clients: Clients) {
+async fn sniff(
+    mut sniffer: Sniffer,
+    packets: Packets,
+    rules: DecodeRules,
+    classifier: Classifier,
+    clients: Clients,
+) {
     while let Some(result) = sniffer.next_packet().await {
         match result {
             Ok(mut pack) => {
-                pack.guess_payload();
+                // held until the packet is stored, so that the reclassification
+                // doesn't miss packets already seen by the classifier
+                let mut classifier = classifier.lock().await;
+                let confirmed_ids = match DecodeRule::find(&rules.read().await, &pack) {
+                    Some(rule) => {
+                        pack.parse_as(rule.protocol);
+                        Vec::new()
+                    }
+                    None => classifier.classify(&mut pack),
+                };
                 // TODO: Packet send via WebSocket contains its
                 // payload, which is undesired
                 let response = Response::Packet(pack);
@@ -165,21 +250,29 @@ async fn sniff(mut sniffer: Sniffer, packets: Packets, clients: Clients) {
                     error!("Sniffer: failed to encode packet");
                     continue;
                 };
-                let msg = Message::binary(encoded);
-                for (_, client) in clients.read().await.iter() {
-                    match client {
-                        Client {
-                            source: Some(source),
-                            sender,
-                        } if *source == sniffer.source => {
-                            sender.send(msg.clone()).unwrap_or_else(|e| {
-                                error!("Sniffer: error while sending packet: {}", e);
-                            });
-                        }
-                        _ => {}
-                    }
+                send_to_source_clients(&clients, &sniffer.source, Message::binary(encoded)).await;
+
+                let mut packets = packets.write().await;
+                packets.push(response);
+
+                // previous packets of a newly confirmed RTP flow
+                // were sent as unknown, so they need to be sent again
+                for id in confirmed_ids {
+                    let Some(index) = find_packet_index(&packets, id) else {
+                        continue;
+                    };
+                    let Response::Packet(packet) = &mut packets[index] else {
+                        unreachable!("only packets are stored");
+                    };
+                    packet.parse_as(SessionProtocol::Rtp);
+
+                    let Ok(encoded) = packets[index].encode() else {
+                        error!("Sniffer: failed to encode packet");
+                        continue;
+                    };
+                    send_to_source_clients(&clients, &sniffer.source, Message::binary(encoded))
+                        .await;
                 }
-                packets.write().await.push(response);
             }
             Err(err) => info!("Error when capturing a packet: {:?}", err),
         }
@@ -189,6 +282,9 @@ async fn sniff(mut sniffer: Sniffer, packets: Packets, clients: Clients) {
 async fn send_all_packets(
     client_id: usize,
     packets: &Packets,
+    sdps: &Sdps,
+    rules: &DecodeRules,
+    quality_rules: &QualityRules,
     ws_tx: &mut UnboundedSender<Message>,
 ) {
     for pack in packets.read().await.iter() {
@@ -202,6 +298,35 @@ async fn send_all_packets(
         });
     }
 
+    // SDPs are sent after the packets, so that the client
+    // already knows about the streams they apply to
+    for (stream_key, sdp) in sdps.read().await.iter() {
+        let Ok(encoded) = Response::Sdp(*stream_key, sdp.clone()).encode() else {
+            error!("Failed to encode sdp, client_id: {}", client_id);
+            continue;
+        };
+        let msg = Message::binary(encoded);
+        ws_tx.send(msg).unwrap_or_else(|e| {
+            error!("WebSocket `feed` error: {}, client_id: {}", e, client_id);
+        });
+    }
+
+    let Ok(encoded) = Response::DecodeRules(rules.read().await.clone()).encode() else {
+        error!("Failed to encode decode rules, client_id: {}", client_id);
+        return;
+    };
+    ws_tx.send(Message::binary(encoded)).unwrap_or_else(|e| {
+        error!("WebSocket `feed` error: {}, client_id: {}", e, client_id);
+    });
+
+    let Ok(encoded) = Response::QualityRules(quality_rules.read().await.clone()).encode() else {
+        error!("Failed to encode quality rules, client_id: {}", client_id);
+        return;
+    };
+    ws_tx.send(Message::binary(encoded)).unwrap_or_else(|e| {
+        error!("WebSocket `feed` error: {}, client_id: {}", e, client_id);
+    });
+
     info!(
         "Sucesfully send already captured packets, client_id: {}",
         client_id
@@ -217,7 +342,7 @@ async fn reparse_packet(
     packet_type: SessionProtocol,
 ) {
     let mut packets = packets.write().await;
-    let Some(response_packet) = packets.get_mut(id) else {
+    let Some(index) = find_packet_index(&packets, id) else {
         warn!(
             "Received reparse request for non-existent packet {}, client_id: {}",
             id, client_id
@@ -225,34 +350,33 @@ async fn reparse_packet(
         return;
     };
 
-    let Response::Packet(packet) = response_packet else {
-        unreachable!("");
+    let Response::Packet(packet) = &mut packets[index] else {
+        unreachable!("only packets are stored");
     };
     packet.parse_as(packet_type);
 
-    let Ok(encoded) = response_packet.encode() else {
+    let Ok(encoded) = packets[index].encode() else {
         error!("Failed to encode packet, client_id: {}", client_id);
         return;
     };
-    let msg = Message::binary(encoded);
-    for (_, client) in clients.read().await.iter() {
-        match client {
-            Client {
-                source: Some(source),
-                sender,
-            } if *source == *cur_source => {
-                sender.send(msg.clone()).unwrap_or_else(|e| {
-                    error!("Sniffer: error while sending packet: {}", e);
-                });
-            }
-            _ => {}
-        };
-    }
+    send_to_source_clients(clients, cur_source, Message::binary(encoded)).await;
+}
+
+// packets are stored in the order of their ids, but not every id
+// is present, as some of the captured packets fail to parse
+fn find_packet_index(packets: &[Response], id: usize) -> Option<usize> {
+    packets
+        .binary_search_by_key(&id, |response| match response {
+            Response::Packet(packet) => packet.id,
+            _ => unreachable!("only packets are stored"),
+        })
+        .ok()
 }
 
 async fn parse_sdp(
     client_id: usize,
     clients: &Clients,
+    sdps: &Sdps,
     cur_source: &Source,
     stream_key: StreamKey,
     raw_sdp: String,
@@ -266,20 +390,203 @@ async fn parse_sdp(
         return;
     };
 
+    // replaces the previously applied SDP, if there was one
+    sdps.write().await.insert(stream_key, sdp.clone());
+
     let Ok(encoded) = Response::Sdp(stream_key, sdp).encode() else {
         error!("Failed to encode sdp, client_id: {}", client_id);
         return;
     };
 
+    send_to_source_clients(clients, cur_source, Message::binary(encoded)).await;
+}
+
+async fn remove_sdp(
+    client_id: usize,
+    clients: &Clients,
+    sdps: &Sdps,
+    cur_source: &Source,
+    stream_key: StreamKey,
+) {
+    if sdps.write().await.remove(&stream_key).is_none() {
+        warn!(
+            "Received remove request for non-existent SDP {:?}, client_id: {}",
+            stream_key, client_id
+        );
+        return;
+    }
+
+    let Ok(encoded) = Response::SdpRemoved(stream_key).encode() else {
+        error!("Failed to encode sdp removal, client_id: {}", client_id);
+        return;
+    };
+
+    send_to_source_clients(clients, cur_source, Message::binary(encoded)).await;
+}
+
+async fn add_decode_rule(
+    clients: &Clients,
+    packets: &Packets,
+    rules: &DecodeRules,
+    classifier: &Classifier,
+    cur_source: &Source,
+    rule: DecodeRule,
+) {
+    let mut wr_rules = rules.write().await;
+    wr_rules.retain(|other| other.target != rule.target);
+    wr_rules.push(rule);
+    let new_rules = wr_rules.clone();
+    std::mem::drop(wr_rules);
+
+    apply_decode_rules(clients, packets, classifier, cur_source, new_rules).await;
+}
+
+async fn remove_decode_rule(
+    client_id: usize,
+    clients: &Clients,
+    packets: &Packets,
+    rules: &DecodeRules,
+    classifier: &Classifier,
+    cur_source: &Source,
+    target: RuleTarget,
+) {
+    let mut wr_rules = rules.write().await;
+    let count = wr_rules.len();
+    wr_rules.retain(|rule| rule.target != target);
+    if wr_rules.len() == count {
+        warn!(
+            "Received remove request for non-existent decode rule {}, client_id: {}",
+            target, client_id
+        );
+        return;
+    }
+    let new_rules = wr_rules.clone();
+    std::mem::drop(wr_rules);
+
+    apply_decode_rules(clients, packets, classifier, cur_source, new_rules).await;
+}
+
+// sends the packets changed by the new rules and the rules themselves,
+// the live capture continues with the state of the reclassification
+async fn apply_decode_rules(
+    clients: &Clients,
+    packets: &Packets,
+    classifier: &Classifier,
+    cur_source: &Source,
+    rules: Vec<DecodeRule>,
+) {
+    let mut classifier = classifier.lock().await;
+    let mut packets = packets.write().await;
+    let (changed, new_classifier) = reclassify_packets(&mut packets, &rules);
+    *classifier = new_classifier;
+
+    let messages: Vec<_> = changed
+        .into_iter()
+        .filter_map(|index| match packets[index].encode() {
+            Ok(encoded) => Some(Message::binary(encoded)),
+            Err(_) => {
+                error!("Failed to encode packet");
+                None
+            }
+        })
+        .collect();
+    std::mem::drop(packets);
+    std::mem::drop(classifier);
+
+    for msg in messages {
+        send_to_source_clients(clients, cur_source, msg).await;
+    }
+
+    let Ok(encoded) = Response::DecodeRules(rules).encode() else {
+        error!("Failed to encode decode rules");
+        return;
+    };
+    send_to_source_clients(clients, cur_source, Message::binary(encoded)).await;
+}
+
+// packets not matched by any of the rules are classified again,
+// as if they were just captured (which discards the effect of `Reparse` requests),
+// returns indices of packets that changed their session protocol and the classifier
+// that has seen all of the packets
+fn reclassify_packets(
+    packets: &mut [Response],
+    rules: &[DecodeRule],
+) -> (Vec<usize>, FlowClassifier) {
+    let previous: Vec<_> = packets.iter().map(get_session_protocol).collect();
+
+    let mut classifier = FlowClassifier::default();
+    for index in 0..packets.len() {
+        let Response::Packet(packet) = &mut packets[index] else {
+            unreachable!("only packets are stored");
+        };
+        packet.parse_as(SessionProtocol::Unknown);
+
+        let confirmed_ids = match DecodeRule::find(rules, packet) {
+            Some(rule) => {
+                packet.parse_as(rule.protocol);
+                continue;
+            }
+            None => classifier.classify(packet),
+        };
+
+        for id in confirmed_ids {
+            let Some(confirmed_index) = find_packet_index(packets, id) else {
+                continue;
+            };
+            let Response::Packet(confirmed) = &mut packets[confirmed_index] else {
+                unreachable!("only packets are stored");
+            };
+            confirmed.parse_as(SessionProtocol::Rtp);
+        }
+    }
+
+    let changed = packets
+        .iter()
+        .zip(previous)
+        .enumerate()
+        .filter(|(_, (response, protocol))| get_session_protocol(response) != *protocol)
+        .map(|(index, _)| index)
+        .collect();
+
+    (changed, classifier)
+}
+
+fn get_session_protocol(response: &Response) -> SessionProtocol {
+    match response {
+        Response::Packet(packet) => packet.session_protocol,
+        _ => unreachable!("only packets are stored"),
+    }
+}
+
+// quality rules apply to all of the sources
+async fn set_quality_rules(
+    clients: &Clients,
+    quality_rules: &QualityRules,
+    new_rules: Vec<QualityRule>,
+) {
+    *quality_rules.write().await = new_rules.clone();
+
+    let Ok(encoded) = Response::QualityRules(new_rules).encode() else {
+        error!("Failed to encode quality rules");
+        return;
+    };
     let msg = Message::binary(encoded);
-    for (_, client) in clients.read().await.iter() {
+    for client in clients.read().await.values() {
+        client.sender.send(msg.clone()).unwrap_or_else(|e| {
+            error!("Error while sending message: {}", e);
+        });
+    }
+}
+
+async fn send_to_source_clients(clients: &Clients, cur_source: &Source, msg: Message) {
+    for client in clients.read().await.values() {
         match client {
             Client {
                 source: Some(source),
                 sender,
             } if *source == *cur_source => {
                 sender.send(msg.clone()).unwrap_or_else(|e| {
-                    error!("Sniffer: error while sending sdp: {}", e);
+                    error!("Error while sending message: {}", e);
                 });
             }
             _ => {}
@@ -287,11 +594,16 @@ async fn parse_sdp(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_messages(
     client_id: usize,
     mut ws_rx: SplitStream<WebSocket>,
     clients: &Clients,
     packets: &PacketsMap,
+    sdps: &SdpsMap,
+    rules: &DecodeRulesMap,
+    classifiers: &ClassifiersMap,
+    quality_rules: &QualityRules,
 ) {
     // we could also simply pass the tx and source as function arguments
     // but it doesn't really matter
@@ -319,7 +631,17 @@ async fn handle_messages(
                     Request::FetchAll => {
                         if let Some(ref cur_source) = source {
                             let packets = packets.get(cur_source).unwrap();
-                            send_all_packets(client_id, packets, &mut sender).await;
+                            let sdps = sdps.get(cur_source).unwrap();
+                            let rules = rules.get(cur_source).unwrap();
+                            send_all_packets(
+                                client_id,
+                                packets,
+                                sdps,
+                                rules,
+                                quality_rules,
+                                &mut sender,
+                            )
+                            .await;
                         }
                     }
                     Request::Reparse(id, packet_type) => {
@@ -342,6 +664,8 @@ async fn handle_messages(
                     }
                     Request::ChangeSource(new_source) => {
                         let packets = packets.get(&new_source).unwrap();
+                        let sdps = sdps.get(&new_source).unwrap();
+                        let rules = rules.get(&new_source).unwrap();
 
                         source = Some(new_source);
                         let mut wr_clients = clients.write().await;
@@ -349,13 +673,51 @@ async fn handle_messages(
                         client.source = source.clone();
                         std::mem::drop(wr_clients);
 
-                        send_all_packets(client_id, packets, &mut sender).await;
+                        send_all_packets(
+                            client_id,
+                            packets,
+                            sdps,
+                            rules,
+                            quality_rules,
+                            &mut sender,
+                        )
+                        .await;
                     }
                     Request::ParseSdp(stream_key, sdp) => {
                         if let Some(source) = &source {
-                            parse_sdp(client_id, clients, source, stream_key, sdp).await;
+                            let sdps = sdps.get(source).unwrap();
+                            parse_sdp(client_id, clients, sdps, source, stream_key, sdp).await;
+                        }
+                    }
+                    Request::RemoveSdp(stream_key) => {
+                        if let Some(source) = &source {
+                            let sdps = sdps.get(source).unwrap();
+                            remove_sdp(client_id, clients, sdps, source, stream_key).await;
+                        }
+                    }
+                    Request::AddDecodeRule(rule) => {
+                        if let Some(source) = &source {
+                            let packets = packets.get(source).unwrap();
+                            let rules = rules.get(source).unwrap();
+                            let classifier = classifiers.get(source).unwrap();
+                            add_decode_rule(clients, packets, rules, classifier, source, rule)
+                                .await;
+                        }
+                    }
+                    Request::RemoveDecodeRule(target) => {
+                        if let Some(source) = &source {
+                            let packets = packets.get(source).unwrap();
+                            let rules = rules.get(source).unwrap();
+                            let classifier = classifiers.get(source).unwrap();
+                            remove_decode_rule(
+                                client_id, clients, packets, rules, classifier, source, target,
+                            )
+                            .await;
                         }
                     }
+                    Request::SetQualityRules(new_rules) => {
+                        set_quality_rules(clients, quality_rules, new_rules).await;
+                    }
                 };
             }
             Err(e) => error!("WebSocket error: {}, client_id: {}", e, client_id),